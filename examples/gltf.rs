@@ -1,5 +1,5 @@
-use elikar_scene::{Camera3D, mesh::MeshResource};
-use xecs::{query::WithId, world::World};
+use elikar_scene::{Camera3D, Mesh, mesh::MeshData};
+use xecs::{query::WithId, World};
 
 fn main() {
     let mut world = World::new();
@@ -8,15 +8,17 @@ fn main() {
 
     elikar_scene::gltf::load_scene(&world,"gltf_files/test.gltf").unwrap();
 
-    for (id,mesh_resource) in world.query::<&mut MeshResource>().with_id() {
-        let (indices,_vertices) = mesh_resource.fetch().unwrap();
-        let _indices = indices.unwrap();
-        println!("mesh resource id {}:",id);
-        // println!("indices {:?},attributes:{:?},size:{},data:{:?}",
-                 // &indices,
-                 // vertices.attributes(),
-                 // vertices.data().len(),
-                 // vertices.data());
+    for (id,mesh) in world.query::<&Mesh>().with_id() {
+        println!("mesh id {}:",id);
+        println!("mesh data id:{}",mesh.mesh_data_id());
+    }
+
+    for (id,mesh_data) in world.query::<&MeshData>().with_id() {
+        println!("mesh data id {}:",id);
+        println!("indices {:?},vertices:{:?},size:{}",
+                 mesh_data.indices.as_ref().map(|_| ()),
+                 mesh_data.vertices,
+                 mesh_data.data.len());
     }
 
     for (id,camera) in world.query::<&Camera3D>().with_id() {
@@ -0,0 +1,255 @@
+use nalgebra_glm as glm;
+use xecs::{EntityId, World, query::WithId};
+use crate::{Transform3D, mesh::MorphWeights};
+
+/// Resource
+/// A set of channels played together
+#[derive(Debug,Clone,PartialEq)]
+pub struct AnimationClip {
+    pub name: Option<String>,
+    pub channels: Vec<Channel>,
+}
+
+/// Animate one property of an entity with keyframes
+#[derive(Debug,Clone,PartialEq)]
+pub struct Channel {
+    /// The animated entity
+    pub target: EntityId,
+    pub property: Property,
+    pub interpolation: Interpolation,
+    /// Keyframe times in seconds, in ascending order
+    pub inputs: Vec<f32>,
+    /// Flattened keyframe values.
+    /// In cubic spline, every keyframe has in-tangent, value and out-tangent.
+    pub outputs: Vec<f32>,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Property {
+    Translation,
+    Rotation,
+    Scale,
+    MorphWeights,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Interpolation {
+    Step,
+    /// Rotations are interpolated with slerp
+    Linear,
+    CubicSpline,
+}
+
+/// A sampled value of channel
+#[derive(Debug,Clone,PartialEq)]
+pub enum Sample {
+    Translation(glm::TVec3<f32>),
+    Rotation(glm::Qua<f32>),
+    Scale(glm::TVec3<f32>),
+    MorphWeights(Vec<f32>),
+}
+
+impl AnimationClip {
+    /// The time of last keyframe
+    pub fn duration(&self) -> f32 {
+        self.channels.iter()
+            .filter_map(|channel| channel.inputs.last())
+            .fold(0.0,|duration,time| duration.max(*time))
+    }
+
+    /// Sample every channel at `time`
+    pub fn sample(&self,time: f32) -> Vec<(EntityId,Sample)> {
+        self.channels.iter()
+            .map(|channel| (channel.target,channel.sample(time)))
+            .collect()
+    }
+}
+
+impl Channel {
+    /// The number of components in a keyframe value
+    fn components(&self) -> usize {
+        let values = match self.interpolation {
+            Interpolation::CubicSpline => self.inputs.len() * 3,
+            _ => self.inputs.len(),
+        };
+        self.outputs.len().checked_div(values).unwrap_or(0)
+    }
+
+    /// Get the `element`th element of `keyframe`.
+    /// `element` is 0 for in-tangent, 1 for value, 2 for out-tangent in cubic spline
+    fn element(&self,keyframe: usize,element: usize) -> &[f32] {
+        let components = self.components();
+        let index = match self.interpolation {
+            Interpolation::CubicSpline => keyframe * 3 + element,
+            _ => keyframe,
+        };
+        &self.outputs[index * components..(index + 1) * components]
+    }
+
+    fn value(&self,keyframe: usize) -> &[f32] {
+        match self.interpolation {
+            Interpolation::CubicSpline => self.element(keyframe,1),
+            _ => self.element(keyframe,0),
+        }
+    }
+
+    /// Sample the channel at `time`, clamped to the keyframe range
+    pub fn sample(&self,time: f32) -> Sample {
+        let last = self.inputs.len().saturating_sub(1);
+        let next = self.inputs.partition_point(|input| *input <= time);
+        let values = if next == 0 || next > last {
+            self.value(next.min(last)).to_vec()
+        } else {
+            let previous = next - 1;
+            let delta = self.inputs[next] - self.inputs[previous];
+            let t = (time - self.inputs[previous]) / delta;
+            match self.interpolation {
+                Interpolation::Step => self.value(previous).to_vec(),
+                Interpolation::Linear if self.property == Property::Rotation => {
+                    let from = glm::make_quat(self.value(previous));
+                    let to = glm::make_quat(self.value(next));
+                    glm::quat_slerp(&from,&to,t).coords.as_slice().to_vec()
+                },
+                Interpolation::Linear => self.value(previous).iter()
+                    .zip(self.value(next))
+                    .map(|(from,to)| from + (to - from) * t)
+                    .collect(),
+                Interpolation::CubicSpline => {
+                    let t2 = t * t;
+                    let t3 = t2 * t;
+                    let p0 = self.value(previous);
+                    let m0 = self.element(previous,2);
+                    let p1 = self.value(next);
+                    let m1 = self.element(next,0);
+                    (0..p0.len())
+                        .map(|i| (2.0 * t3 - 3.0 * t2 + 1.0) * p0[i]
+                            + (t3 - 2.0 * t2 + t) * delta * m0[i]
+                            + (-2.0 * t3 + 3.0 * t2) * p1[i]
+                            + (t3 - t2) * delta * m1[i])
+                        .collect()
+                },
+            }
+        };
+        match self.property {
+            Property::Translation => Sample::Translation(glm::make_vec3(&values)),
+            Property::Rotation => Sample::Rotation(glm::quat_normalize(&glm::make_quat(&values))),
+            Property::Scale => Sample::Scale(glm::make_vec3(&values)),
+            Property::MorphWeights => Sample::MorphWeights(values),
+        }
+    }
+}
+
+/// Play an AnimationClip on its target entities
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct AnimationPlayer {
+    /// The AnimationClip entity
+    pub clip: EntityId,
+    /// Current time in seconds
+    pub time: f32,
+    /// Playback speed multiplier
+    pub speed: f32,
+    /// Restart from the beginning after the end
+    pub looping: bool,
+}
+
+impl AnimationPlayer {
+    pub fn new(clip: EntityId) -> Self {
+        AnimationPlayer {
+            clip,
+            time: 0.0,
+            speed: 1.0,
+            looping: true,
+        }
+    }
+
+    /// Advance the time by `delta` seconds
+    pub fn advance(&mut self,delta: f32,duration: f32) {
+        self.time += delta * self.speed;
+        if self.looping && duration > 0.0 {
+            self.time = self.time.rem_euclid(duration);
+        } else {
+            self.time = self.time.clamp(0.0,duration);
+        }
+    }
+
+    /// Sample the clip at current time and write the result into target entities
+    pub fn apply(&self,world: &World) {
+        let clip = world.entity_component_read::<AnimationClip>(self.clip)
+            .expect("AnimationPlayer: The clip is not an AnimationClip");
+        for (target,sample) in clip.sample(self.time) {
+            apply_sample(world,target,sample);
+        }
+    }
+}
+
+fn apply_sample(world: &World,target: EntityId,sample: Sample) {
+    let transform = || world.entity_component_write::<Transform3D>(target);
+    match sample {
+        Sample::Translation(translation) => if let Some(mut transform) = transform() {
+            transform.translation = translation;
+        },
+        Sample::Rotation(rotation) => if let Some(mut transform) = transform() {
+            transform.rotation = rotation;
+        },
+        Sample::Scale(scale) => if let Some(mut transform) = transform() {
+            transform.scale = scale;
+        },
+        Sample::MorphWeights(weights) => if let Some(mut morph_weights) = world.entity_component_write::<MorphWeights>(target) {
+            morph_weights.0 = weights;
+        },
+    }
+}
+
+/// Advance every AnimationPlayer by `delta` seconds and apply them
+pub fn play_animations(world: &World,delta: f32) {
+    let players = world.query::<&mut AnimationPlayer>()
+        .with_id()
+        .map(|(id,_)| id)
+        .collect::<Vec<_>>();
+    for id in players {
+        let mut player = world.entity_component_write::<AnimationPlayer>(id).unwrap();
+        let duration = world.entity_component_read::<AnimationClip>(player.clip)
+            .expect("AnimationPlayer: The clip is not an AnimationClip")
+            .duration();
+        player.advance(delta,duration);
+        let player = *player;
+        player.apply(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use nalgebra_glm as glm;
+    use super::{Channel, Property, Interpolation, Sample};
+
+    fn channel(property: Property,interpolation: Interpolation,outputs: Vec<f32>) -> Channel {
+        Channel {
+            target: NonZeroUsize::new(1).unwrap(),
+            property,
+            interpolation,
+            inputs: vec![0.0, 1.0],
+            outputs,
+        }
+    }
+
+    #[test]
+    fn sample() {
+        let linear = channel(Property::Translation,Interpolation::Linear,vec![0.0,0.0,0.0, 2.0,4.0,6.0]);
+        assert_eq!(linear.sample(0.5), Sample::Translation(glm::vec3(1.0,2.0,3.0)));
+        assert_eq!(linear.sample(-1.0), Sample::Translation(glm::vec3(0.0,0.0,0.0)));
+        assert_eq!(linear.sample(2.0), Sample::Translation(glm::vec3(2.0,4.0,6.0)));
+
+        let step = channel(Property::Scale,Interpolation::Step,vec![1.0,1.0,1.0, 2.0,2.0,2.0]);
+        assert_eq!(step.sample(0.9), Sample::Scale(glm::vec3(1.0,1.0,1.0)));
+
+        // in-tangent, value, out-tangent of two keyframes
+        let cubic = channel(Property::MorphWeights,Interpolation::CubicSpline,vec![0.0,0.0,0.0, 0.0,1.0,0.0]);
+        assert_eq!(cubic.sample(0.5), Sample::MorphWeights(vec![0.5]));
+
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let rotation = channel(Property::Rotation,Interpolation::Linear,vec![0.0,0.0,0.0,1.0, 0.0,0.0,1.0,0.0]);
+        let Sample::Rotation(rotation) = rotation.sample(0.5) else { unreachable!() };
+        assert!((rotation.coords - glm::vec4(0.0,0.0,half,half)).norm() < 1e-5);
+    }
+}
@@ -0,0 +1,230 @@
+use nalgebra_glm as glm;
+use xecs::{World, EntityId, StorageRead, query::WithId};
+use crate::{Mesh, Node, GlobalTransform3D};
+
+/// Axis-aligned bounding box
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct Aabb {
+    pub min: glm::TVec3<f32>,
+    pub max: glm::TVec3<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: glm::TVec3<f32>,max: glm::TVec3<f32>) -> Self {
+        Aabb {
+            min,
+            max,
+        }
+    }
+
+    /// The smallest box containing `points`, None if there is no point
+    pub fn from_points<I: IntoIterator<Item = [f32; 3]>>(points: I) -> Option<Self> {
+        points.into_iter()
+            .map(|point| {
+                let point = glm::make_vec3(&point);
+                Aabb::new(point,point)
+            })
+            .reduce(|aabb,point| aabb.merge(&point))
+    }
+
+    pub fn center(&self) -> glm::TVec3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> glm::TVec3<f32> {
+        (self.max - self.min) * 0.5
+    }
+
+    /// The smallest box containing both boxes
+    pub fn merge(&self,other: &Aabb) -> Aabb {
+        Aabb::new(glm::min2(&self.min,&other.min),glm::max2(&self.max,&other.max))
+    }
+
+    /// The axis-aligned box containing this box transformed by `matrix`
+    pub fn transform(&self,matrix: &glm::TMat4<f32>) -> Aabb {
+        let center = (matrix * self.center().push(1.0)).xyz();
+        let linear = glm::mat4_to_mat3(matrix).abs();
+        let half_extents = linear * self.half_extents();
+        Aabb::new(center - half_extents,center + half_extents)
+    }
+}
+
+/// Bounding sphere
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct Sphere {
+    pub center: glm::TVec3<f32>,
+    pub radius: f32,
+}
+
+impl Sphere {
+    pub fn new(center: glm::TVec3<f32>,radius: f32) -> Self {
+        Sphere {
+            center,
+            radius,
+        }
+    }
+
+    /// The sphere containing this sphere transformed by `matrix`.
+    /// The radius is scaled by the largest scale of `matrix`
+    pub fn transform(&self,matrix: &glm::TMat4<f32>) -> Sphere {
+        let center = (matrix * self.center.push(1.0)).xyz();
+        let scale = (0..3)
+            .map(|column| glm::length(&matrix.column(column).xyz()))
+            .fold(0.0,f32::max);
+        Sphere::new(center,self.radius * scale)
+    }
+}
+
+/// The world-space bounds of a Mesh entity, written by [update_bounds]
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct Bounds {
+    aabb: Aabb,
+    sphere: Sphere,
+    /// The mesh data the local bounds are computed from
+    data_id: EntityId,
+    local_aabb: Aabb,
+    local_sphere: Sphere,
+    /// The global matrix the world-space bounds are computed with
+    matrix: glm::TMat4<f32>,
+}
+
+impl Bounds {
+    pub fn aabb(&self) -> &Aabb {
+        &self.aabb
+    }
+
+    pub fn sphere(&self) -> &Sphere {
+        &self.sphere
+    }
+}
+
+/// Compute Bounds of the entities with Mesh and GlobalTransform3D.
+/// Bounds is attached if it's missing.
+/// The mesh-space sphere is cached and only recomputed when the Mesh points at other mesh data
+/// or [MeshData::aabb](crate::mesh::MeshData::aabb) changed.
+/// Entities whose global matrix and mesh-space box are unchanged are skipped.
+/// Call it after [propagate_transforms](crate::propagate_transforms)
+pub fn update_bounds(world: &World) {
+    let bounds = {
+        let existing = world.components_read::<Bounds>()
+            .expect("Bounds is not registered");
+        world.query::<(&Mesh,&GlobalTransform3D)>()
+            .with_id()
+            .filter_map(|(id,(mesh,global))| {
+                let data_id = mesh.mesh_data_id();
+                let matrix = *global.matrix();
+                // The box is cheap when it's given by the source,
+                // and editing positions with set_attribute clears it
+                let mesh_data = mesh.mesh_data_read(world);
+                let local_aabb = mesh_data.aabb()?;
+                let local_sphere = match existing.get(id) {
+                    Some(bounds) if bounds.data_id == data_id && bounds.local_aabb == local_aabb => {
+                        if bounds.matrix == matrix {
+                            return None;
+                        }
+                        bounds.local_sphere
+                    },
+                    _ => mesh_data.bounding_sphere()?,
+                };
+                let bounds = Bounds {
+                    aabb: local_aabb.transform(&matrix),
+                    sphere: local_sphere.transform(&matrix),
+                    data_id,
+                    local_aabb,
+                    local_sphere,
+                    matrix,
+                };
+                Some((id,bounds))
+            })
+            .collect::<Vec<_>>()
+    };
+    for (id,bounds) in bounds {
+        // The storage lock is released when the component is missing
+        match world.entity_component_write::<Bounds>(id) {
+            Some(mut existing) => *existing = bounds,
+            None => world.attach_component(id,bounds),
+        }
+    }
+}
+
+/// The world-space box containing the Bounds of `root` and all its descendants.
+/// Return None if there is no Bounds in the subtree
+pub fn subtree_bounds(world: &World,root: EntityId) -> Option<Aabb> {
+    let nodes = world.components_read::<Node>()
+        .expect("Node is not registered");
+    let bounds = world.components_read::<Bounds>()
+        .expect("Bounds is not registered");
+    merge_subtree(root,&nodes,&bounds)
+}
+
+fn merge_subtree(id: EntityId,nodes: &StorageRead<Node>,bounds: &StorageRead<Bounds>) -> Option<Aabb> {
+    let aabb = bounds.get(id).map(|bounds| bounds.aabb);
+    nodes.get(id)
+        .map(Node::children)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|&child| merge_subtree(child,nodes,bounds))
+        .fold(aabb,|aabb,child| Some(aabb.map_or(child,|aabb| aabb.merge(&child))))
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+    use xecs::World;
+    use crate::{Transform3D, Node, Mesh, propagate_transforms};
+    use crate::mesh::{MeshData, AttributeType, DataType, f32_bytes};
+    use super::{Aabb, Bounds, update_bounds, subtree_bounds};
+
+    #[test]
+    fn subtree() {
+        let mut world = World::new();
+        crate::init(&mut world);
+        let mut mesh_data = MeshData::default();
+        mesh_data.set_attribute(AttributeType::Position,DataType::Float32x3,
+            &f32_bytes(&[-1.0,0.0,0.0, 1.0,0.0,0.0, 0.0,1.0,0.0, 0.0,-1.0,0.0]));
+        assert_eq!(mesh_data.aabb(), Some(Aabb::new(glm::vec3(-1.0,-1.0,0.0),glm::vec3(1.0,1.0,0.0))));
+        assert_eq!(mesh_data.bounding_sphere().unwrap().radius, 1.0);
+        let data_id = world.create_entity()
+            .attach(mesh_data)
+            .into_id();
+        let parent = world.create_entity()
+            .attach(Transform3D::new())
+            .attach(Mesh::from_data(data_id))
+            .into_id();
+        let mut child_transform = Transform3D::new();
+        child_transform.move_to(0.0,0.0,5.0);
+        child_transform.scale_to(2.0,2.0,2.0);
+        let child = world.create_entity()
+            .attach(child_transform)
+            .attach(Mesh::from_data(data_id))
+            .attach(Node::from_parent(parent))
+            .into_id();
+        let mut node = Node::new();
+        node.add_child(child);
+        world.attach_component(parent,node);
+
+        propagate_transforms(&world);
+        update_bounds(&world);
+        let bounds = *world.entity_component_read::<Bounds>(child).unwrap();
+        assert_eq!(*bounds.aabb(), Aabb::new(glm::vec3(-2.0,-2.0,5.0),glm::vec3(2.0,2.0,5.0)));
+        assert_eq!(bounds.sphere().radius, 2.0);
+        assert_eq!(subtree_bounds(&world,parent), Some(Aabb::new(glm::vec3(-2.0,-2.0,0.0),glm::vec3(2.0,2.0,5.0))));
+
+        // Moving the parent moves the cached bounds of the child
+        world.entity_component_write::<Transform3D>(parent).unwrap().move_to(1.0,0.0,0.0);
+        propagate_transforms(&world);
+        update_bounds(&world);
+        let bounds = *world.entity_component_read::<Bounds>(child).unwrap();
+        assert_eq!(*bounds.aabb(), Aabb::new(glm::vec3(-1.0,-2.0,5.0),glm::vec3(3.0,2.0,5.0)));
+        assert_eq!(bounds.sphere().center, glm::vec3(1.0,0.0,5.0));
+
+        // Editing the mesh data updates the bounds without moving
+        world.entity_component_read::<Mesh>(parent).unwrap()
+            .mesh_data_write(&world)
+            .set_attribute(AttributeType::Position,DataType::Float32x3,&f32_bytes(&[0.0,0.0,0.0, 0.0,0.0,3.0]));
+        update_bounds(&world);
+        let bounds = *world.entity_component_read::<Bounds>(child).unwrap();
+        assert_eq!(*bounds.aabb(), Aabb::new(glm::vec3(1.0,0.0,5.0),glm::vec3(1.0,0.0,11.0)));
+        assert_eq!(bounds.sphere().radius, 3.0);
+    }
+}
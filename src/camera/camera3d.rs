@@ -1,23 +1,126 @@
 use nalgebra_glm as glm;
+use crate::{Transform3D, GlobalTransform3D};
+use super::Frustum;
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Projection {
+    Perspective {
+        /// None means the aspect ratio of viewport is used
+        aspect: Option<f32>,
+        fov_y: f32,
+        near: f32,
+        /// None means an infinite projection
+        far: Option<f32>,
+    },
+    Orthographic {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    },
+}
 
 pub struct Camera3D {
-    projection : glm::TMat4<f32>
+    projection : Projection,
+    /// The aspect ratio of viewport
+    viewport_aspect : f32,
+    matrix : glm::TMat4<f32>
 }
 
 impl Camera3D {
+    pub fn new(projection : Projection) -> Self {
+        let mut camera = Camera3D {
+            projection,
+            viewport_aspect: 1.0,
+            matrix: glm::identity(),
+        };
+        camera.update_matrix();
+        camera
+    }
+
     pub fn perspective(aspect : f32,fov_y : f32,near : f32,far : f32) -> Self {
-        Camera3D {
-            projection: glm::perspective(aspect, fov_y, near, far),
-        }
+        Camera3D::new(Projection::Perspective {
+            aspect: Some(aspect),
+            fov_y,
+            near,
+            far: Some(far),
+        })
+    }
+
+    /// Perspective projection with an infinite far plane
+    pub fn infinite_perspective(aspect : f32,fov_y : f32,near : f32) -> Self {
+        Camera3D::new(Projection::Perspective {
+            aspect: Some(aspect),
+            fov_y,
+            near,
+            far: None,
+        })
     }
 
     pub fn ortho(left : f32,right : f32,bottom : f32,top : f32,near : f32,far : f32) -> Self {
-        Camera3D {
-            projection: glm::ortho(left, right, bottom, top, near, far),
-        }
+        Camera3D::new(Projection::Orthographic {
+            left,
+            right,
+            bottom,
+            top,
+            near,
+            far,
+        })
     }
 
-    pub fn projection_matrix(&self) -> &glm::TMat4<f32> {
+    pub fn projection(&self) -> &Projection {
         &self.projection
     }
+
+    /// Update the aspect ratio with the size of viewport.
+    /// Only affects perspective projection without aspect ratio.
+    pub fn set_viewport(&mut self,width : f32,height : f32) {
+        self.viewport_aspect = width / height;
+        self.update_matrix();
+    }
+
+    pub fn projection_matrix(&self) -> &glm::TMat4<f32> {
+        &self.matrix
+    }
+
+    /// The view matrix of a camera placed by `transform`, looking toward -Z of its local space.
+    /// The camera is at the origin of its local space, which is moved by the `center` pivot.
+    /// The scale of `transform` is ignored
+    pub fn view_matrix(transform : &Transform3D) -> glm::TMat4<f32> {
+        view_from_model(&transform.model_matrix())
+    }
+
+    /// The view matrix of a camera placed by the world-space transform
+    pub fn global_view_matrix(global : &GlobalTransform3D) -> glm::TMat4<f32> {
+        view_from_model(global.matrix())
+    }
+
+    /// The projection matrix multiplied by the view matrix of the world-space transform
+    pub fn view_projection(&self,global : &GlobalTransform3D) -> glm::TMat4<f32> {
+        self.matrix * Camera3D::global_view_matrix(global)
+    }
+
+    /// The world-space frustum of the camera placed by `global`
+    pub fn frustum(&self,global : &GlobalTransform3D) -> Frustum {
+        Frustum::from_matrix(&self.view_projection(global))
+    }
+
+    fn update_matrix(&mut self) {
+        self.matrix = match self.projection {
+            Projection::Perspective { aspect, fov_y, near, far: Some(far) } =>
+                glm::perspective(aspect.unwrap_or(self.viewport_aspect), fov_y, near, far),
+            Projection::Perspective { aspect, fov_y, near, far: None } =>
+                glm::infinite_perspective_rh_no(aspect.unwrap_or(self.viewport_aspect), fov_y, near),
+            Projection::Orthographic { left, right, bottom, top, near, far } =>
+                glm::ortho(left, right, bottom, top, near, far),
+        };
+    }
+}
+
+/// Invert the rigid part of a model matrix
+fn view_from_model(model : &glm::TMat4<f32>) -> glm::TMat4<f32> {
+    let decomposed = Transform3D::from_matrix(model);
+    glm::quat_to_mat4(&glm::quat_conjugate(&decomposed.rotation)) * glm::translation(&-decomposed.translation)
 }
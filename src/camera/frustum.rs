@@ -0,0 +1,117 @@
+use nalgebra_glm as glm;
+use crate::bounds::{Aabb, Sphere};
+
+/// A plane with `dot(normal, p) + distance == 0`, the normal points inside the frustum
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct Plane {
+    pub normal: glm::TVec3<f32>,
+    pub distance: f32,
+}
+
+impl Plane {
+    /// The signed distance from `point`, positive on the side of the normal
+    pub fn signed_distance(&self,point: &glm::TVec3<f32>) -> f32 {
+        glm::dot(&self.normal,point) + self.distance
+    }
+}
+
+/// The six planes bounding the visible volume of a camera
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct Frustum {
+    /// Left, right, bottom, top, near and far planes
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    /// Extract the planes from a view-projection matrix with clip depth in [-1, 1].
+    /// The far plane of an infinite projection contains everything
+    pub fn from_matrix(matrix: &glm::TMat4<f32>) -> Self {
+        let row = |index: usize| matrix.row(index).transpose();
+        let (x,y,z,w) = (row(0),row(1),row(2),row(3));
+        let planes = [w + x,w - x,w + y,w - y,w + z,w - z].map(|plane| {
+            let length = glm::length(&plane.xyz());
+            let plane = if length > 0.0 { plane / length } else { plane };
+            Plane {
+                normal: plane.xyz(),
+                distance: plane.w,
+            }
+        });
+        Frustum {
+            planes,
+        }
+    }
+
+    pub fn contains_point(&self,point: &glm::TVec3<f32>) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    /// Whether the sphere is inside or intersects the frustum
+    pub fn intersects_sphere(&self,sphere: &Sphere) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(&sphere.center) >= -sphere.radius)
+    }
+
+    /// Whether the box is inside or intersects the frustum.
+    /// Boxes near the corners outside of the frustum may be reported as intersecting
+    pub fn intersects_aabb(&self,aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // The corner farthest along the normal
+            let corner = glm::vec3(
+                if plane.normal.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if plane.normal.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if plane.normal.z >= 0.0 { aabb.max.z } else { aabb.min.z });
+            plane.signed_distance(&corner) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+    use xecs::World;
+    use crate::{Camera3D, Transform3D, GlobalTransform3D, Node, propagate_transforms};
+    use crate::bounds::{Aabb, Sphere};
+
+    #[test]
+    fn culling() {
+        // Turn around the pivot at (1, 0, 0), so the camera is at (2, 0, 0) looking toward +Z
+        let mut transform = Transform3D::new();
+        transform.center = glm::vec3(1.0,0.0,0.0);
+        transform.rotate_y_by(std::f32::consts::PI);
+        let view = Camera3D::view_matrix(&transform);
+        let eye = view * glm::vec4(2.0,0.0,0.0,1.0);
+        assert!(glm::length(&eye.xyz()) < 1e-5);
+
+        // Parent the camera, 1 unit along its local +Z, which is -Z in world space
+        let mut world = World::new();
+        crate::init(&mut world);
+        let parent = world.create_entity()
+            .attach(transform)
+            .into_id();
+        let mut camera_transform = Transform3D::new();
+        camera_transform.move_to(0.0,0.0,1.0);
+        let camera = world.create_entity()
+            .attach(Camera3D::perspective(1.0,std::f32::consts::FRAC_PI_2,0.1,100.0))
+            .attach(camera_transform)
+            .attach(Node::from_parent(parent))
+            .into_id();
+        let mut node = Node::new();
+        node.add_child(camera);
+        world.attach_component(parent,node);
+        propagate_transforms(&world);
+        let global = *world.entity_component_read::<GlobalTransform3D>(camera).unwrap();
+        let eye = Camera3D::global_view_matrix(&global) * glm::vec4(2.0,0.0,-1.0,1.0);
+        assert!(glm::length(&eye.xyz()) < 1e-5);
+
+        let frustum = world.entity_component_read::<Camera3D>(camera).unwrap().frustum(&global);
+        assert!(frustum.contains_point(&glm::vec3(2.0,0.0,10.0)));
+        assert!(!frustum.contains_point(&glm::vec3(2.0,0.0,-10.0)));
+        assert!(frustum.intersects_sphere(&Sphere::new(glm::vec3(2.0,0.0,-1.0),2.0)));
+        assert!(!frustum.intersects_sphere(&Sphere::new(glm::vec3(2.0,0.0,200.0),1.0)));
+        assert!(frustum.intersects_aabb(&Aabb::new(glm::vec3(-10.0,-1.0,5.0),glm::vec3(-5.0,1.0,20.0))));
+        assert!(!frustum.intersects_aabb(&Aabb::new(glm::vec3(20.0,-1.0,5.0),glm::vec3(30.0,1.0,6.0))));
+
+        // The far plane of an infinite projection contains everything
+        let camera = Camera3D::infinite_perspective(1.0,std::f32::consts::FRAC_PI_2,0.1);
+        assert!(camera.frustum(&global).contains_point(&glm::vec3(2.0,0.0,1e6)));
+    }
+}
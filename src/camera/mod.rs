@@ -1,5 +1,7 @@
 mod camera2d;
 mod camera3d;
+mod frustum;
 
 pub use camera2d::Camera2D;
-pub use camera3d::Camera3D;
+pub use camera3d::{Camera3D, Projection};
+pub use frustum::{Frustum, Plane};
@@ -0,0 +1,141 @@
+use gltf::{Accessor, accessor::{DataType, sparse::IndexType}, buffer::View};
+use super::Error;
+
+/// Read the elements of an accessor into a tightly packed byte buffer.
+/// The byte offset of accessor and the stride of buffer view are respected.
+/// Sparse accessors are materialized into dense data.
+pub fn read(accessor: &Accessor,buffers: &[Vec<u8>]) -> Result<Vec<u8>,Error> {
+    let size = accessor.size();
+    let count = accessor.count();
+    let mut data = if let Some(view) = accessor.view() {
+        let view_data = view_data(&view,buffers)?;
+        let stride = view.stride().unwrap_or(size);
+        let mut data = Vec::with_capacity(size * count);
+        for index in 0..count {
+            let begin = accessor.offset() + index * stride;
+            let element = view_data.get(begin..begin + size)
+                .ok_or(Error::BufferViewOutOfRange)?;
+            data.extend_from_slice(element);
+        }
+        data
+    } else {
+        // Accessors without buffer view are initialized with zeros
+        vec![0;size * count]
+    };
+    if let Some(sparse) = accessor.sparse() {
+        let indices = sparse.indices();
+        let index_size = match indices.index_type() {
+            IndexType::U8 => 1,
+            IndexType::U16 => 2,
+            IndexType::U32 => 4,
+        };
+        let indices_data = view_data(&indices.view(),buffers)?
+            .get(indices.offset()..)
+            .ok_or(Error::BufferViewOutOfRange)?;
+        let values = sparse.values();
+        let values_data = view_data(&values.view(),buffers)?
+            .get(values.offset()..)
+            .ok_or(Error::BufferViewOutOfRange)?;
+        for index in 0..sparse.count() {
+            let bytes = indices_data.get(index * index_size..(index + 1) * index_size)
+                .ok_or(Error::BufferViewOutOfRange)?;
+            let target = match bytes {
+                [b0] => *b0 as usize,
+                [b0,b1] => u16::from_le_bytes([*b0,*b1]) as usize,
+                [b0,b1,b2,b3] => u32::from_le_bytes([*b0,*b1,*b2,*b3]) as usize,
+                _ => unreachable!(),
+            };
+            let value = values_data.get(index * size..(index + 1) * size)
+                .ok_or(Error::BufferViewOutOfRange)?;
+            data.get_mut(target * size..(target + 1) * size)
+                .ok_or(Error::BufferViewOutOfRange)?
+                .copy_from_slice(value);
+        }
+    }
+    Ok(data)
+}
+
+/// Read the components of an accessor as f32.
+/// Normalized integers are converted to [0, 1] or [-1, 1], other integers are casted
+pub fn read_f32(accessor: &Accessor,buffers: &[Vec<u8>]) -> Result<Vec<f32>,Error> {
+    let data = read(accessor,buffers)?;
+    let normalized = accessor.normalized();
+    let normalize = |value: f32,max: f32| if normalized { (value / max).max(-1.0) } else { value };
+    Ok(match accessor.data_type() {
+        DataType::F32 => data.chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]))
+            .collect(),
+        DataType::U8 => data.iter()
+            .map(|byte| normalize(*byte as f32,255.0))
+            .collect(),
+        DataType::I8 => data.iter()
+            .map(|byte| normalize(*byte as i8 as f32,127.0))
+            .collect(),
+        DataType::U16 => data.chunks_exact(2)
+            .map(|bytes| normalize(u16::from_le_bytes([bytes[0],bytes[1]]) as f32,65535.0))
+            .collect(),
+        DataType::I16 => data.chunks_exact(2)
+            .map(|bytes| normalize(i16::from_le_bytes([bytes[0],bytes[1]]) as f32,32767.0))
+            .collect(),
+        DataType::U32 => data.chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]) as f32)
+            .collect(),
+    })
+}
+
+fn view_data<'a>(view: &View,buffers: &'a [Vec<u8>]) -> Result<&'a [u8],Error> {
+    buffers.get(view.buffer().index())
+        .and_then(|buffer| buffer.get(view.offset()..view.offset() + view.length()))
+        .ok_or(Error::BufferViewOutOfRange)
+}
+
+#[cfg(test)]
+mod tests {
+    use gltf::Gltf;
+    use super::read;
+
+    #[test]
+    fn shared_view() {
+        let gltf = Gltf::from_slice(br#"{
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 16 }],
+            "bufferViews": [{ "buffer": 0, "byteOffset": 4, "byteLength": 12, "byteStride": 4 }],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5121, "count": 2, "type": "VEC2" },
+                { "bufferView": 0, "byteOffset": 2, "componentType": 5121, "count": 3, "type": "VEC2" }
+            ]
+        }"#).unwrap();
+        let buffers = vec![(0..16).collect::<Vec<u8>>()];
+        let accessors = gltf.accessors().collect::<Vec<_>>();
+        assert_eq!(read(&accessors[0],&buffers).unwrap(), vec![4,5,8,9]);
+        assert_eq!(read(&accessors[1],&buffers).unwrap(), vec![6,7,10,11,14,15]);
+    }
+
+    #[test]
+    fn sparse() {
+        let gltf = Gltf::from_slice(br#"{
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 8 }],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 4 },
+                { "buffer": 0, "byteOffset": 4, "byteLength": 4 }
+            ],
+            "accessors": [
+                { "componentType": 5121, "count": 4, "type": "SCALAR", "sparse": {
+                    "count": 2,
+                    "indices": { "bufferView": 0, "byteOffset": 2, "componentType": 5121 },
+                    "values": { "bufferView": 1, "byteOffset": 1 }
+                } },
+                { "bufferView": 1, "componentType": 5121, "count": 4, "type": "SCALAR", "sparse": {
+                    "count": 1,
+                    "indices": { "bufferView": 0, "componentType": 5121 },
+                    "values": { "bufferView": 0, "byteOffset": 3 }
+                } }
+            ]
+        }"#).unwrap();
+        let buffers = vec![vec![0,1,1,3, 4,5,6,7]];
+        let accessors = gltf.accessors().collect::<Vec<_>>();
+        assert_eq!(read(&accessors[0],&buffers).unwrap(), vec![0,5,0,6]);
+        assert_eq!(read(&accessors[1],&buffers).unwrap(), vec![3,5,6,7]);
+    }
+}
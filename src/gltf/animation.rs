@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+use gltf::{Gltf, animation};
+use xecs::{World, EntityId};
+use crate::animation::{AnimationClip, Channel, Property, Interpolation};
+use super::{Error, accessor, node::mesh_entities};
+
+/// Create an AnimationClip entity for each animation.
+/// Channels targeting nodes which are not in the scene are ignored
+pub fn load_animations(gltf: &Gltf,world: &World,buffers: &[Vec<u8>],nodes: &[Option<EntityId>]) -> Result<Vec<EntityId>,Error> {
+    let node_entities = nodes.iter()
+        .flatten()
+        .copied()
+        .collect::<HashSet<_>>();
+    let mut animations = Vec::new();
+    for animation in gltf.animations() {
+        let mut channels = Vec::new();
+        for channel in animation.channels() {
+            let Some(target) = nodes[channel.target().node().index()] else {
+                continue;
+            };
+            let sampler = channel.sampler();
+            let channel = Channel {
+                target,
+                property: match channel.target().property() {
+                    animation::Property::Translation => Property::Translation,
+                    animation::Property::Rotation => Property::Rotation,
+                    animation::Property::Scale => Property::Scale,
+                    animation::Property::MorphTargetWeights => Property::MorphWeights,
+                },
+                interpolation: match sampler.interpolation() {
+                    animation::Interpolation::Step => Interpolation::Step,
+                    animation::Interpolation::Linear => Interpolation::Linear,
+                    animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+                },
+                inputs: accessor::read_f32(&sampler.input(),buffers)?,
+                outputs: accessor::read_f32(&sampler.output(),buffers)?,
+            };
+            if channel.property == Property::MorphWeights {
+                // Morph weights are applied to every primitive of the mesh
+                for target in mesh_entities(world,target,&node_entities) {
+                    channels.push(Channel {
+                        target,
+                        ..channel.clone()
+                    });
+                }
+            } else {
+                channels.push(channel);
+            }
+        }
+        let clip = AnimationClip {
+            name: animation.name().map(String::from),
+            channels,
+        };
+        let id = world.create_entity()
+            .attach(clip)
+            .into_id();
+        animations.push(id);
+    }
+    Ok(animations)
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+    use xecs::{World, query::WithId};
+    use crate::{AnimationClip, AnimationPlayer, Transform3D, MorphWeights, Node};
+    use crate::animation::Property;
+    use crate::gltf::{LoadOptions, fixture};
+
+    #[test]
+    fn import() {
+        let mut buffer = fixture::f32_bytes(&[0.0,0.0,0.0, 1.0,0.0,0.0, 0.0,1.0,0.0]);
+        buffer.extend(fixture::f32_bytes(&[0.0,0.0,1.0, 0.0,0.0,1.0, 0.0,0.0,1.0]));
+        // times, rotations and weights
+        buffer.extend(fixture::f32_bytes(&[0.0, 1.0]));
+        buffer.extend(fixture::f32_bytes(&[0.0,0.0,0.0,1.0, 0.0,0.0,1.0,0.0]));
+        buffer.extend(fixture::f32_bytes(&[0.0, 1.0]));
+        let primitive = r#"{ "attributes": { "POSITION": 0 }, "targets": [{ "POSITION": 1 }] }"#;
+        let json = format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "buffers": [{{ "byteLength": {}, "uri": "{}" }}],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 72 }},
+                {{ "buffer": 0, "byteOffset": 72, "byteLength": 48 }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0,0,0], "max": [1,1,0] }},
+                {{ "bufferView": 0, "byteOffset": 36, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                {{ "bufferView": 1, "componentType": 5126, "count": 2, "type": "SCALAR", "min": [0], "max": [1] }},
+                {{ "bufferView": 1, "byteOffset": 8, "componentType": 5126, "count": 2, "type": "VEC4" }},
+                {{ "bufferView": 1, "byteOffset": 40, "componentType": 5126, "count": 2, "type": "SCALAR" }}
+            ],
+            "meshes": [{{ "primitives": [{primitive}, {primitive}] }}],
+            "nodes": [{{ "mesh": 0 }}],
+            "animations": [{{
+                "name": "spin",
+                "channels": [
+                    {{ "sampler": 0, "target": {{ "node": 0, "path": "rotation" }} }},
+                    {{ "sampler": 1, "target": {{ "node": 0, "path": "weights" }} }}
+                ],
+                "samplers": [
+                    {{ "input": 2, "output": 3 }},
+                    {{ "input": 2, "output": 4 }}
+                ]
+            }}]
+        }}"#,buffer.len(),fixture::data_uri(&buffer));
+        let mut world = World::new();
+        crate::init(&mut world);
+        let roots = fixture::load(&world,json.as_bytes(),&LoadOptions::default()).unwrap();
+        let primitive = world.entity_component_read::<Node>(roots[0]).unwrap().children()[0];
+        let (clip,_) = world.query::<&AnimationClip>().with_id().next().unwrap();
+        {
+            let clip = world.entity_component_read::<AnimationClip>(clip).unwrap();
+            assert_eq!(clip.name.as_deref(), Some("spin"));
+            assert_eq!(clip.duration(), 1.0);
+            // The morph weights channel is fanned out to every primitive
+            let targets = clip.channels.iter()
+                .map(|channel| (channel.target,channel.property))
+                .collect::<Vec<_>>();
+            assert_eq!(targets, vec![
+                (roots[0],Property::Rotation),
+                (roots[0],Property::MorphWeights),
+                (primitive,Property::MorphWeights),
+            ]);
+        }
+
+        let mut player = AnimationPlayer::new(clip);
+        player.time = 0.5;
+        player.apply(&world);
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let rotation = world.entity_component_read::<Transform3D>(roots[0]).unwrap().rotation;
+        assert!((rotation.coords - glm::vec4(0.0,0.0,half,half)).norm() < 1e-5);
+        assert_eq!(world.entity_component_read::<MorphWeights>(roots[0]).unwrap().0, vec![0.5]);
+        assert_eq!(world.entity_component_read::<MorphWeights>(primitive).unwrap().0, vec![0.5]);
+    }
+}
@@ -1,27 +1,20 @@
-use std::{path::Path, borrow::Cow, fs::File, io::Read};
+use std::path::Path;
 use gltf::{Gltf, buffer};
-use super::Error;
+use super::{Error, uri};
 
 
 pub fn load_buffers(gltf: &Gltf,gltf_path: &Path) -> Result<Vec<Vec<u8>>, Error> {
     let mut buffers = Vec::new();
     for buffer in gltf.buffers() {
         match buffer.source() {
-            buffer::Source::Bin => todo!(),
-            buffer::Source::Uri(buffer_path) => {
-                let mut path = Cow::Borrowed(gltf_path);
-                let buffer_path = Path::new(buffer_path);
-                let buffer_path = if buffer_path.is_relative() {
-                    path.to_mut().pop();
-                    path.to_mut().push(buffer_path);
-                    path.as_ref()
-                } else {
-                    buffer_path
-                };
-                let mut file = File::open(buffer_path)?;
-                let mut v = Vec::new();
-                file.read_to_end(&mut v)?;
-                buffers.push(v);
+            buffer::Source::Bin => {
+                // The BIN chunk of a .glb file is always the first buffer
+                let blob = gltf.blob.as_ref()
+                    .ok_or(Error::MissingBinaryChunk)?;
+                buffers.push(blob.clone());
+            },
+            buffer::Source::Uri(uri) => {
+                buffers.push(uri::read(uri,gltf_path)?);
             },
         }
     }
@@ -5,13 +5,16 @@ pub enum Error {
     GltfError(gltf::Error),
     IoError(io::Error),
     UnsupportedIndicesFormat,
-    UnsupportedSemantic(gltf::Semantic),
     UnsupportedDataTypeOrDimensions,
     UnsupportedBufferLayout,
     UnsupportedImageFormat,
-    UnsupportedSparseStorage,
+    MissingBinaryChunk,
+    BufferViewOutOfRange,
+    UnsupportedUri,
+    MissingJoint,
     PngDecodingError(png::DecodingError),
-    JpegDecodingError(jpeg_decoder::Error)
+    JpegDecodingError(jpeg_decoder::Error),
+    Base64DecodingError(base64::DecodeError)
 }
 
 impl From<gltf::Error> for Error {
@@ -38,6 +41,12 @@ impl From<jpeg_decoder::Error> for Error {
     }
 }
 
+impl From<base64::DecodeError> for Error {
+    fn from(err: base64::DecodeError) -> Self {
+        Error::Base64DecodingError(err)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -47,20 +56,26 @@ impl Display for Error {
                 write!(f,"Loading GLTF file failed: The indices format is not supported"),
             Error::IoError(err) =>
                 write!(f,"Loading GLTF file failed: {}",err),
-            Error::UnsupportedSemantic(semantic) =>
-                write!(f,"Loading GLTF file failed: The semantic {:?} is not supported",semantic),
             Error::UnsupportedDataTypeOrDimensions =>
                 write!(f,"Loading GLTF file failed: The data type or Dimensions are not supported"),
             Error::UnsupportedBufferLayout =>
                 write!(f,"Loading GLTF file failed: The buffer layout is not supported"),
             Error::UnsupportedImageFormat =>
                 write!(f,"Loading GLTF file failed: The image format is not supported"),
-            Error::UnsupportedSparseStorage =>
-                write!(f,"Loading GLTF file failed: The sparse feature is not supported"),
+            Error::MissingBinaryChunk =>
+                write!(f,"Loading GLTF file failed: The BIN chunk is missing"),
+            Error::BufferViewOutOfRange =>
+                write!(f,"Loading GLTF file failed: The buffer view is out of range"),
+            Error::UnsupportedUri =>
+                write!(f,"Loading GLTF file failed: The uri is not supported"),
+            Error::MissingJoint =>
+                write!(f,"Loading GLTF file failed: The joints of skin are not in the scene"),
             Error::PngDecodingError(err) =>
                 write!(f,"Loading GLTF file failed: {}",err),
             Error::JpegDecodingError(err) =>
                 write!(f,"Loading GLTF file failed: {}",err),
+            Error::Base64DecodingError(err) =>
+                write!(f,"Loading GLTF file failed: {}",err),
         }
     }
 }
@@ -72,6 +87,7 @@ impl std::error::Error for Error {
             Error::IoError(err) => Some(err),
             Error::PngDecodingError(err) => Some(err),
             Error::JpegDecodingError(err) => Some(err),
+            Error::Base64DecodingError(err) => Some(err),
             _ => None
         }
     }
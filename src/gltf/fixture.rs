@@ -0,0 +1,49 @@
+//! Helpers building glTF files in memory for tests
+use std::path::Path;
+use gltf::Gltf;
+use xecs::{World, EntityId};
+use super::{Error, LoadOptions};
+
+pub(crate) use crate::mesh::f32_bytes;
+
+/// Embed a buffer in a base64 data uri
+pub fn data_uri(data: &[u8]) -> String {
+    format!("data:application/octet-stream;base64,{}",base64::encode(data))
+}
+
+/// Pack a JSON chunk and a BIN chunk into a GLB file
+pub fn glb(json: &str,bin: &[u8]) -> Vec<u8> {
+    let mut json = json.as_bytes().to_vec();
+    json.resize(json.len().next_multiple_of(4),b' ');
+    let mut bin = bin.to_vec();
+    bin.resize(bin.len().next_multiple_of(4),0);
+    let length = 12 + 8 + json.len() + 8 + bin.len();
+    let mut glb = Vec::with_capacity(length);
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(length as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json);
+    glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&bin);
+    glb
+}
+
+pub fn png(width: u32,height: u32,color_type: png::ColorType,bit_depth: png::BitDepth,data: &[u8]) -> Vec<u8> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png,width,height);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(data).unwrap();
+    writer.finish().unwrap();
+    png
+}
+
+/// Load a glTF or GLB file in memory, all buffers and images must be embedded
+pub fn load(world: &World,data: &[u8],options: &LoadOptions) -> Result<Vec<EntityId>,Error> {
+    let gltf = super::validate(Gltf::from_slice_without_validation(data)?)?;
+    super::load(world,&gltf,Path::new(""),options)
+}
@@ -1,83 +1,180 @@
-use std::{path::Path, borrow::Cow, fs::File};
+use std::{path::Path, io::Read};
 use gltf::{Gltf, image};
 use xecs::{World, EntityId};
 use crate::{Image, image::ColorType};
-use super::Error;
+use super::{Error, uri};
 
-pub fn load_images(gltf: &Gltf,gltf_path: &Path,world: &World) -> Result<Vec<EntityId>,Error> {
+pub fn load_images(gltf: &Gltf,gltf_path: &Path,world: &World,buffers: &[Vec<u8>]) -> Result<Vec<EntityId>,Error> {
     let mut images = Vec::new();
     for image in gltf.images() {
-        match image.source() {
-            image::Source::View { .. } => todo!(),
-            image::Source::Uri { uri, .. } => {
-                let mut path = Cow::Borrowed(gltf_path);
-                let image_path = Path::new(uri);
-                let image_path = if image_path.is_relative() {
-                    path.to_mut().pop();
-                    path.to_mut().push(image_path);
-                    path.as_ref()
-                } else {
-                    image_path
-                };
-                let ext = image_path.extension()
-                    .ok_or(Error::UnsupportedImageFormat)?;
-                if ext == "png" || ext == "PNG" {
-                    let file = File::open(image_path)?;
-                    let png_decoder = png::Decoder::new(file);
-                    let mut reader = png_decoder.read_info()?;
-                    let mut buffer = vec![0;reader.output_buffer_size()];
-                    let info = reader.next_frame(&mut buffer)?;
-                    if info.color_type == png::ColorType::Rgb &&
-                       info.bit_depth == png::BitDepth::Eight {
-                        let image = Image {
-                            width: info.width,
-                            height: info.height,
-                            color_type: ColorType::RGB,
-                            data: buffer,
-                        };
-                        let id = world.create_entity()
-                            .attach(image)
-                            .into_id();
-                        images.push(id);
-                    } else if info.color_type == png::ColorType::Rgba &&
-                              info.bit_depth == png::BitDepth::Eight {
-                        let image = Image {
-                            width: info.width,
-                            height: info.height,
-                            color_type: ColorType::RGBA,
-                            data: buffer,
-                        };
-                        let id = world.create_entity()
-                            .attach(image)
-                            .into_id();
-                        images.push(id);
-                    } else {
-                        return Err(Error::UnsupportedImageFormat);
-                    }
-                } else if ext == "jpg" || ext == "JPG" || ext == "jpeg" || ext == "JPEG" {
-                    let file = File::open(image_path)?;
-                    let mut jpeg_decoder = jpeg_decoder::Decoder::new(file);
-                    jpeg_decoder.read_info()?;
-                    let info = jpeg_decoder.info().unwrap();
-                    if info.pixel_format == jpeg_decoder::PixelFormat::RGB24 {
-                        let image = Image {
-                            width: info.width as _,
-                            height: info.height as _,
-                            color_type: ColorType::RGB,
-                            data: jpeg_decoder.decode()?,
-                        };
-                        let id = world.create_entity()
-                            .attach(image)
-                            .into_id();
-                        images.push(id);
-                    } else {
-                        return Err(Error::UnsupportedImageFormat);
-                    }
-                } else {
-                    return Err(Error::UnsupportedImageFormat)
+        let image = match image.source() {
+            image::Source::View { view, mime_type } => {
+                // Images in buffer views must be tightly packed
+                if view.stride().is_some() {
+                    return Err(Error::UnsupportedBufferLayout);
                 }
+                let data = buffers.get(view.buffer().index())
+                    .and_then(|buffer| buffer.get(view.offset()..view.offset() + view.length()))
+                    .ok_or(Error::BufferViewOutOfRange)?;
+                decode(data,mime_type)?
             },
-        }
+            image::Source::Uri { uri, mime_type } => {
+                let data = uri::read(uri,gltf_path)?;
+                // The MIME type of a data uri takes precedence,
+                // and fall back to the extension of file
+                let mime_type = uri::mime_type(uri)
+                    .filter(|mime_type| mime_type.starts_with("image/"))
+                    .or(mime_type)
+                    .or_else(|| mime_type_from_extension(uri))
+                    .ok_or(Error::UnsupportedImageFormat)?;
+                decode(&data,mime_type)?
+            },
+        };
+        let id = world.create_entity()
+            .attach(image)
+            .into_id();
+        images.push(id);
     }
     Ok(images)
-}
\ No newline at end of file
+}
+
+fn mime_type_from_extension(uri: &str) -> Option<&'static str> {
+    let ext = Path::new(uri).extension()?;
+    if ext == "png" || ext == "PNG" {
+        Some("image/png")
+    } else if ext == "jpg" || ext == "JPG" || ext == "jpeg" || ext == "JPEG" {
+        Some("image/jpeg")
+    } else {
+        None
+    }
+}
+
+fn decode(data: &[u8],mime_type: &str) -> Result<Image,Error> {
+    match mime_type {
+        "image/png" => decode_png(data),
+        "image/jpeg" => decode_jpeg(data),
+        _ => Err(Error::UnsupportedImageFormat)
+    }
+}
+
+fn decode_png<R: Read>(reader: R) -> Result<Image,Error> {
+    let mut png_decoder = png::Decoder::new(reader);
+    // expand palette and low bit depth images and strip 16 bits images to 8 bits
+    png_decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = png_decoder.read_info()?;
+    let mut buffer = vec![0;reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+    // palettes are already expanded to RGB or RGBA
+    let (color_type,data) = match info.color_type.samples() {
+        1 => (ColorType::RGB,gray_to_rgb(&buffer)),
+        2 => (ColorType::RGBA,gray_alpha_to_rgba(&buffer)),
+        3 => (ColorType::RGB,buffer),
+        _ => (ColorType::RGBA,buffer),
+    };
+    Ok(Image {
+        width: info.width,
+        height: info.height,
+        color_type,
+        data,
+    })
+}
+
+fn decode_jpeg<R: Read>(reader: R) -> Result<Image,Error> {
+    let mut jpeg_decoder = jpeg_decoder::Decoder::new(reader);
+    jpeg_decoder.read_info()?;
+    let info = jpeg_decoder.info().unwrap();
+    let data = jpeg_decoder.decode()?;
+    let data = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => gray_to_rgb(&data),
+        jpeg_decoder::PixelFormat::RGB24 => data,
+        _ => return Err(Error::UnsupportedImageFormat),
+    };
+    Ok(Image {
+        width: info.width as _,
+        height: info.height as _,
+        color_type: ColorType::RGB,
+        data,
+    })
+}
+
+fn gray_to_rgb(data: &[u8]) -> Vec<u8> {
+    data.iter()
+        .flat_map(|&l| [l,l,l])
+        .collect()
+}
+
+fn gray_alpha_to_rgba(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(2)
+        .flat_map(|la| [la[0],la[0],la[0],la[1]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use png::{ColorType, BitDepth};
+    use crate::image;
+    use super::decode;
+    use crate::gltf::fixture;
+
+    fn decode_png(width: u32,color_type: ColorType,bit_depth: BitDepth,data: &[u8]) -> crate::Image {
+        decode(&fixture::png(width,1,color_type,bit_depth,data),"image/png").unwrap()
+    }
+
+    #[test]
+    fn png_color_types() {
+        let rgb = decode_png(1,ColorType::Rgb,BitDepth::Eight,&[1,2,3]);
+        assert_eq!((rgb.color_type,rgb.data), (image::ColorType::RGB,vec![1,2,3]));
+        let rgba = decode_png(1,ColorType::Rgba,BitDepth::Eight,&[1,2,3,4]);
+        assert_eq!((rgba.color_type,rgba.data), (image::ColorType::RGBA,vec![1,2,3,4]));
+        let gray = decode_png(2,ColorType::Grayscale,BitDepth::Eight,&[7,9]);
+        assert_eq!((gray.color_type,gray.data), (image::ColorType::RGB,vec![7,7,7, 9,9,9]));
+        let gray_alpha = decode_png(1,ColorType::GrayscaleAlpha,BitDepth::Eight,&[7,128]);
+        assert_eq!((gray_alpha.color_type,gray_alpha.data), (image::ColorType::RGBA,vec![7,7,7,128]));
+        // 16 bits samples are stripped to their high bytes
+        let gray16 = decode_png(1,ColorType::Grayscale,BitDepth::Sixteen,&[0x12,0x34]);
+        assert_eq!(gray16.data, vec![0x12,0x12,0x12]);
+    }
+
+    /// A baseline grayscale 8x8 JPEG, every coefficient is 0 so all pixels are 128
+    fn gray_jpeg() -> Vec<u8> {
+        let mut data = vec![0xFF,0xD8];
+        // Quantization table 0
+        data.extend_from_slice(&[0xFF,0xDB, 0,67, 0x00]);
+        data.extend_from_slice(&[1;64]);
+        // 8 bits, 8x8, 1 component using quantization table 0
+        data.extend_from_slice(&[0xFF,0xC0, 0,11, 8, 0,8, 0,8, 1, 1,0x11,0]);
+        // DC and AC huffman tables with the single 1 bit code of symbol 0
+        for class in [0x00,0x10] {
+            data.extend_from_slice(&[0xFF,0xC4, 0,20, class, 1]);
+            data.extend_from_slice(&[0;15]);
+            data.push(0);
+        }
+        // The scan: a zero DC difference and an end of block, padded with 1
+        data.extend_from_slice(&[0xFF,0xDA, 0,8, 1, 1,0x00, 0,63,0]);
+        data.extend_from_slice(&[0x3F, 0xFF,0xD9]);
+        data
+    }
+
+    #[test]
+    fn jpeg_gray() {
+        let image = decode(&gray_jpeg(),"image/jpeg").unwrap();
+        assert_eq!((image.width,image.height,image.color_type), (8,8,image::ColorType::RGB));
+        assert_eq!(image.data, vec![128;8 * 8 * 3]);
+    }
+
+    #[test]
+    fn png_palette() {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data,2,1);
+        encoder.set_color(ColorType::Indexed);
+        encoder.set_depth(BitDepth::Eight);
+        encoder.set_palette(vec![10,20,30, 40,50,60]);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[1,0]).unwrap();
+        writer.finish().unwrap();
+        let image = decode(&data,"image/png").unwrap();
+        assert_eq!((image.width,image.height,image.color_type), (2,1,image::ColorType::RGB));
+        assert_eq!(image.data, vec![40,50,60, 10,20,30]);
+    }
+}
@@ -0,0 +1,54 @@
+use gltf::{Gltf, texture, material};
+use xecs::{World, EntityId};
+use crate::material::{Material, PbrMetallicRoughness, TextureInfo, NormalTexture, OcclusionTexture, AlphaMode};
+use super::Error;
+
+pub fn load_materials(gltf: &Gltf,world: &World,textures: &[EntityId]) -> Result<Vec<EntityId>,Error> {
+    let mut materials = Vec::new();
+    for material in gltf.materials() {
+        let pbr = material.pbr_metallic_roughness();
+        let texture_info = |info: Option<texture::Info>| info.and_then(|info| {
+            Some(TextureInfo {
+                texture: *textures.get(info.texture().index())?,
+                tex_coord: info.tex_coord(),
+            })
+        });
+        let material = Material {
+            pbr: PbrMetallicRoughness {
+                base_color_factor: pbr.base_color_factor(),
+                base_color_texture: texture_info(pbr.base_color_texture()),
+                metallic_factor: pbr.metallic_factor(),
+                roughness_factor: pbr.roughness_factor(),
+                metallic_roughness_texture: texture_info(pbr.metallic_roughness_texture()),
+            },
+            normal_texture: material.normal_texture().and_then(|normal| {
+                Some(NormalTexture {
+                    texture: *textures.get(normal.texture().index())?,
+                    tex_coord: normal.tex_coord(),
+                    scale: normal.scale(),
+                })
+            }),
+            occlusion_texture: material.occlusion_texture().and_then(|occlusion| {
+                Some(OcclusionTexture {
+                    texture: *textures.get(occlusion.texture().index())?,
+                    tex_coord: occlusion.tex_coord(),
+                    strength: occlusion.strength(),
+                })
+            }),
+            emissive_texture: texture_info(material.emissive_texture()),
+            emissive_factor: material.emissive_factor(),
+            alpha_mode: match material.alpha_mode() {
+                material::AlphaMode::Opaque => AlphaMode::Opaque,
+                material::AlphaMode::Mask => AlphaMode::Mask,
+                material::AlphaMode::Blend => AlphaMode::Blend,
+            },
+            alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+            double_sided: material.double_sided(),
+        };
+        let id = world.create_entity()
+            .attach(material)
+            .into_id();
+        materials.push(id);
+    }
+    Ok(materials)
+}
@@ -1,11 +1,13 @@
-use std::{slice::from_raw_parts, mem::size_of};
-use gltf::{Gltf, mesh::Mode, Semantic};
+use std::mem::size_of;
+use nalgebra_glm as glm;
+use gltf::{Gltf, Accessor, accessor::Dimensions, mesh::{Mode, Primitive}, Semantic};
 use xecs::{World, EntityId};
-use crate::mesh::{Assembly, Indices, AttributeType, DataType, Attribute, Vertices, MeshData};
-use super::Error;
+use crate::mesh::{Assembly, Indices, AttributeType, DataType, Attribute, Vertices, MeshData, MorphTarget};
+use crate::bounds::Aabb;
+use super::{Error, LoadOptions, accessor};
 
 
-pub fn load_meshes(gltf: &Gltf,world: &World,buffers: &[Vec<u8>],materials: &[EntityId]) -> Result<Vec<Vec<EntityId>>,Error> {
+pub fn load_meshes(gltf: &Gltf,world: &World,buffers: &[Vec<u8>],materials: &[EntityId],options: &LoadOptions) -> Result<Vec<Vec<EntityId>>,Error> {
     let mut ids = Vec::new();
     for mesh in gltf.meshes() {
         ids.push(Vec::new());
@@ -21,81 +23,33 @@ pub fn load_meshes(gltf: &Gltf,world: &World,buffers: &[Vec<u8>],materials: &[En
                 Mode::TriangleFan => Assembly::TriangleFan,
             };
             // Get indices
-            let indices = if let Some(indices) = primitive.indices() {
-                // Get buffer and view
-                let view = indices.view().ok_or(Error::UnsupportedSparseStorage)?;
-                let buffer = &buffers[view.buffer().index()];
-                // Check if the indices are u16 or u32
-                if indices.dimensions() == gltf::accessor::Dimensions::Scalar &&
-                   indices.data_type() == gltf::accessor::DataType::U16 {
-                    // Safety:
-                    // Safe here because data type is U16 and dimensions is Scalar
-                    let slice = unsafe {
-                        from_raw_parts(
-                            buffer.as_ptr().offset(view.offset() as _) as *const u16,
-                            view.length() / size_of::<u16>())
-                    };
-                    Some(Indices::U16(Vec::from(slice)))
-                } else if indices.dimensions() == gltf::accessor::Dimensions::Scalar &&
-                          indices.data_type() == gltf::accessor::DataType::U32 {
-                    // Safety:
-                    // Safe here because data type is U16 and dimensions is Scalar
-                    let slice = unsafe {
-                        from_raw_parts(
-                            buffer.as_ptr().offset(view.offset() as _) as *const u32,
-                            view.length() / size_of::<u32>())
-                    };
-                    Some(Indices::U32(Vec::from(slice)))
-                } else {
-                    return Err(Error::UnsupportedIndicesFormat);
-                }
-            } else {
-                None
-            };
+            let indices = primitive.indices()
+                .map(|indices| read_indices(&indices,buffers))
+                .transpose()?;
             // Get Vertex Attributes
             let mut attributes = Vec::new();
             let mut is_interleaved = false;
-            #[allow(unused)]
-            let mut stride = 0;
             let mut data = Vec::new();
             for (semantic,accessor) in primitive.attributes() {
                 // convert semantic to attribute type
                 let attribute_type = match semantic {
                     Semantic::Positions => AttributeType::Position,
                     Semantic::Normals => AttributeType::Normal,
+                    Semantic::Tangents => AttributeType::Tangent,
                     Semantic::TexCoords(index) => AttributeType::TexCoord(index),
                     Semantic::Colors(_) => AttributeType::Color,
-                    // ignore unsupported attributes
-                    _ => return Err(Error::UnsupportedSemantic(semantic)),
+                    Semantic::Joints(index) => AttributeType::Joints(index),
+                    Semantic::Weights(index) => AttributeType::Weights(index),
                 };
-                let view = accessor.view()
-                    .ok_or(Error::UnsupportedSparseStorage)?;
-                if view.stride().is_some() {
+                // The whole primitive is interleaved if any of its attributes is interleaved
+                if accessor.view().and_then(|view| view.stride()).is_some() {
                     is_interleaved = true;
                 }
-                // Check if mixing the interlace and non-interleaved attributes
-                if is_interleaved {
-                    // Check if mixing the interlace and non-interleaved attributes
-                    if view.stride().is_none() {
-                        todo!("Return error: Unsupported interleaved attributes");
-                    }
-                    todo!("Interleaved attributes");
-                } else {
-                    // Get buffer and view
-                    let buffer = &buffers[view.buffer().index()];
-                    // Safety:
-                    // Safe here because data type is U16 and dimensions is Scalar
-                    let slice = unsafe {
-                        from_raw_parts(
-                            buffer.as_ptr().offset(view.offset() as _) as *const u8,
-                            view.length() * size_of::<u8>())
-                    };
-                    data.extend_from_slice(slice);
-                }
-                let data_type = DataType::try_from_gltf(accessor.data_type(),accessor.dimensions())
-                    .ok_or(Error::UnsupportedDataTypeOrDimensions)?;
+                let (data_type,bytes) = read_attribute(&accessor,attribute_type,buffers,options)?;
                 let count = accessor.count();
-                let offset = view.offset();
+                // offset in MeshData::data
+                let offset = data.len();
+                data.extend_from_slice(&bytes);
                 let attribute = Attribute {
                     ty: attribute_type,
                     data_type,
@@ -104,17 +58,41 @@ pub fn load_meshes(gltf: &Gltf,world: &World,buffers: &[Vec<u8>],materials: &[En
                 };
                 attributes.push(attribute);
             }
-            let vertices = if is_interleaved {
-                Vertices::Interleaved(attributes, stride)
-            } else {
-                Vertices::Separate(attributes)
-            };
+            // All attributes must have the same number of elements
+            if attributes.windows(2).any(|pair| pair[0].count != pair[1].count) {
+                return Err(Error::UnsupportedBufferLayout);
+            }
             let mesh_data = MeshData {
                 data,
+                assembly,
                 indices,
-                vertices,
-                material: todo!(),
+                vertices: Vertices::Separate(attributes),
+                material: primitive.material().index()
+                    .and_then(|index| materials.get(index).copied()),
+                morph_targets: load_morph_targets(&primitive,buffers)?,
+                morph_weights: mesh.weights()
+                    .map(Vec::from)
+                    .unwrap_or_default(),
+                bounds: position_bounds(&primitive),
+            };
+            // Vertex attributes are aligned to 4 bytes in glTF
+            let mut mesh_data = if is_interleaved {
+                mesh_data.into_interleaved(4)
+            } else {
+                mesh_data
             };
+            if options.generate_normals &&
+               mesh_data.attribute_info(AttributeType::Normal).is_none() {
+                mesh_data.compute_flat_normals();
+            }
+            if options.generate_tangents &&
+               mesh_data.attribute_info(AttributeType::Tangent).is_none() {
+                // Tangents follow the texcoords of the normal texture,
+                // meshes without normals or texcoords are kept without tangents
+                let tex_coord = primitive.material().normal_texture()
+                    .map_or(0,|normal| normal.tex_coord());
+                mesh_data.generate_tangents(tex_coord);
+            }
             let mesh_id = world.create_entity()
                 .attach(mesh_data)
                 .into_id();
@@ -123,3 +101,205 @@ pub fn load_meshes(gltf: &Gltf,world: &World,buffers: &[Vec<u8>],materials: &[En
     }
     Ok(ids)
 }
+
+/// Read indices, u8 indices are widened to u16
+fn read_indices(accessor: &Accessor,buffers: &[Vec<u8>]) -> Result<Indices,Error> {
+    if accessor.dimensions() != Dimensions::Scalar {
+        return Err(Error::UnsupportedIndicesFormat);
+    }
+    let data = accessor::read(accessor,buffers)?;
+    Ok(match accessor.data_type() {
+        gltf::accessor::DataType::U8 => Indices::U16(data.iter()
+            .map(|&index| index as u16)
+            .collect()),
+        gltf::accessor::DataType::U16 => Indices::U16(data.chunks_exact(size_of::<u16>())
+            .map(|bytes| u16::from_le_bytes([bytes[0],bytes[1]]))
+            .collect()),
+        gltf::accessor::DataType::U32 => Indices::U32(data.chunks_exact(size_of::<u32>())
+            .map(|bytes| u32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]))
+            .collect()),
+        _ => return Err(Error::UnsupportedIndicesFormat),
+    })
+}
+
+/// Read the data of a vertex attribute and its `DataType`.
+/// Encodings without a matching `DataType` are widened:
+/// 8 and 16 bits vec3 get a zero fourth component, integer scalars become 32 bits
+/// and normalized scalars become float
+fn read_attribute(accessor: &Accessor,attribute_type: AttributeType,buffers: &[Vec<u8>],options: &LoadOptions) -> Result<(DataType,Vec<u8>),Error> {
+    let component_type = accessor.data_type();
+    let quantized = matches!(component_type,
+        gltf::accessor::DataType::U8 | gltf::accessor::DataType::I8 |
+        gltf::accessor::DataType::U16 | gltf::accessor::DataType::I16);
+    let normalize = |data_type: DataType| if accessor.normalized() {
+        data_type.to_normalized()
+    } else {
+        data_type
+    };
+    let dequantize = quantized && options.dequantize && !matches!(attribute_type,AttributeType::Joints(_));
+    let component_size = component_type.size();
+    match (DataType::try_from_gltf(component_type,accessor.dimensions()),accessor.dimensions()) {
+        (Some(data_type),_) if !dequantize => Ok((normalize(data_type),accessor::read(accessor,buffers)?)),
+        (_,Dimensions::Vec3) if quantized && !dequantize => {
+            let data_type = DataType::try_from_gltf(component_type,Dimensions::Vec4)
+                .ok_or(Error::UnsupportedDataTypeOrDimensions)?;
+            let data = accessor::read(accessor,buffers)?
+                .chunks_exact(component_size * 3)
+                .flat_map(|element| element.iter().copied().chain(std::iter::repeat_n(0,component_size)))
+                .collect();
+            Ok((normalize(data_type),data))
+        },
+        (_,Dimensions::Scalar) if quantized && !dequantize && !accessor.normalized() => {
+            let data = accessor::read(accessor,buffers)?;
+            let (data_type,data) = match component_type {
+                gltf::accessor::DataType::U8 => (DataType::Uint32,data.iter()
+                    .flat_map(|&value| (value as u32).to_le_bytes())
+                    .collect()),
+                gltf::accessor::DataType::I8 => (DataType::Sint32,data.iter()
+                    .flat_map(|&value| (value as i8 as i32).to_le_bytes())
+                    .collect()),
+                gltf::accessor::DataType::U16 => (DataType::Uint32,data.chunks_exact(2)
+                    .flat_map(|bytes| (u16::from_le_bytes([bytes[0],bytes[1]]) as u32).to_le_bytes())
+                    .collect()),
+                _ => (DataType::Sint32,data.chunks_exact(2)
+                    .flat_map(|bytes| (i16::from_le_bytes([bytes[0],bytes[1]]) as i32).to_le_bytes())
+                    .collect()),
+            };
+            Ok((data_type,data))
+        },
+        (_,Dimensions::Scalar | Dimensions::Vec2 | Dimensions::Vec3 | Dimensions::Vec4) => {
+            let data_type = DataType::try_from_gltf(gltf::accessor::DataType::F32,accessor.dimensions())
+                .ok_or(Error::UnsupportedDataTypeOrDimensions)?;
+            let data = accessor::read_f32(accessor,buffers)?
+                .into_iter()
+                .flat_map(f32::to_le_bytes)
+                .collect();
+            Ok((data_type,data))
+        },
+        // Matrices are not allowed for vertex attributes
+        _ => Err(Error::UnsupportedDataTypeOrDimensions),
+    }
+}
+
+/// The bounds given by the min and max of float positions.
+/// Quantized positions are left to MeshData::aabb
+fn position_bounds(primitive: &Primitive) -> Option<Aabb> {
+    let positions = primitive.get(&Semantic::Positions)?;
+    if positions.data_type() != gltf::accessor::DataType::F32 {
+        return None;
+    }
+    let bounds = primitive.bounding_box();
+    Some(Aabb::new(glm::make_vec3(&bounds.min),glm::make_vec3(&bounds.max)))
+}
+
+fn load_morph_targets(primitive: &Primitive,buffers: &[Vec<u8>]) -> Result<Vec<MorphTarget>,Error> {
+    let read = |accessor: Option<Accessor>| -> Result<Option<Vec<[f32; 3]>>,Error> {
+        accessor.map(|accessor| {
+            Ok(accessor::read_f32(&accessor,buffers)?
+                .chunks_exact(3)
+                .map(|delta| [delta[0],delta[1],delta[2]])
+                .collect())
+        }).transpose()
+    };
+    primitive.morph_targets()
+        .map(|target| Ok(MorphTarget {
+            positions: read(target.positions())?,
+            normals: read(target.normals())?,
+            tangents: read(target.tangents())?,
+        }))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use gltf::Gltf;
+    use crate::mesh::{Indices, AttributeType, DataType};
+    use crate::gltf::{Error, LoadOptions, fixture};
+    use super::{read_indices, read_attribute};
+
+    #[test]
+    fn indices() {
+        let gltf = Gltf::from_slice(br#"{
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 12 }],
+            "bufferViews": [{ "buffer": 0, "byteLength": 12 }],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5121, "count": 3, "type": "SCALAR" },
+                { "bufferView": 0, "componentType": 5123, "count": 2, "type": "SCALAR" },
+                { "bufferView": 0, "componentType": 5125, "count": 1, "type": "SCALAR" },
+                { "bufferView": 0, "componentType": 5121, "count": 2, "type": "VEC2" },
+                { "bufferView": 0, "componentType": 5122, "count": 1, "type": "SCALAR" }
+            ]
+        }"#).unwrap();
+        let buffers = vec![vec![1,2,3,4, 0,0,0,0, 0,0,0,0]];
+        let accessors = gltf.accessors().collect::<Vec<_>>();
+        assert!(matches!(read_indices(&accessors[0],&buffers), Ok(Indices::U16(indices)) if indices == [1,2,3]));
+        assert!(matches!(read_indices(&accessors[1],&buffers), Ok(Indices::U16(indices)) if indices == [0x0201,0x0403]));
+        assert!(matches!(read_indices(&accessors[2],&buffers), Ok(Indices::U32(indices)) if indices == [0x04030201]));
+        assert!(matches!(read_indices(&accessors[3],&buffers), Err(Error::UnsupportedIndicesFormat)));
+        assert!(matches!(read_indices(&accessors[4],&buffers), Err(Error::UnsupportedIndicesFormat)));
+    }
+
+    #[test]
+    fn widen() {
+        let gltf = Gltf::from_slice(br#"{
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 68 }],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 16, "byteStride": 8 },
+                { "buffer": 0, "byteOffset": 16, "byteLength": 8, "byteStride": 4 },
+                { "buffer": 0, "byteOffset": 24, "byteLength": 2 },
+                { "buffer": 0, "byteOffset": 28, "byteLength": 4 },
+                { "buffer": 0, "byteOffset": 32, "byteLength": 4 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 8 },
+                { "buffer": 0, "byteOffset": 44, "byteLength": 16 }
+            ],
+            "accessors": [
+                { "bufferView": 0, "componentType": 5123, "count": 2, "type": "VEC3" },
+                { "bufferView": 1, "componentType": 5120, "normalized": true, "count": 2, "type": "VEC3" },
+                { "bufferView": 2, "componentType": 5121, "count": 2, "type": "SCALAR" },
+                { "bufferView": 3, "componentType": 5122, "count": 2, "type": "SCALAR" },
+                { "bufferView": 4, "componentType": 5123, "normalized": true, "count": 2, "type": "SCALAR" },
+                { "bufferView": 5, "componentType": 5123, "count": 1, "type": "VEC4" },
+                { "bufferView": 6, "componentType": 5126, "count": 1, "type": "MAT2" }
+            ]
+        }"#).unwrap();
+        let mut buffer = vec![1,0,2,0,3,0,0,0, 4,0,5,0,6,0,0,0];
+        buffer.extend([127,0,129,0, 0,127,0,0]);
+        buffer.extend([7,200,0,0]);
+        buffer.extend([251,255, 44,1]);
+        buffer.extend([0,0, 255,255]);
+        buffer.extend([1,0,2,0,3,0,4,0]);
+        buffer.extend(fixture::f32_bytes(&[1.0,0.0,0.0,1.0]));
+        buffer.resize(68,0);
+        let buffers = vec![buffer];
+        let accessors = gltf.accessors().collect::<Vec<_>>();
+        let options = LoadOptions::default();
+        let dequantize = LoadOptions {
+            dequantize: true,
+            ..LoadOptions::default()
+        };
+        let read = |index: usize,ty: AttributeType,options: &LoadOptions| read_attribute(&accessors[index],ty,&buffers,options);
+        let u32_bytes = |values: &[u32]| values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>();
+
+        // 8 and 16 bits vec3 get a zero fourth component
+        assert_eq!(read(0,AttributeType::Position,&options).unwrap(),
+            (DataType::Uint16x4,vec![1,0,2,0,3,0,0,0, 4,0,5,0,6,0,0,0]));
+        assert_eq!(read(1,AttributeType::Normal,&options).unwrap(),
+            (DataType::Snorm8x4,vec![127,0,129,0, 0,127,0,0]));
+        // Integer scalars become 32 bits and normalized scalars become float
+        assert_eq!(read(2,AttributeType::Color,&options).unwrap(), (DataType::Uint32,u32_bytes(&[7,200])));
+        assert_eq!(read(3,AttributeType::Color,&options).unwrap(), (DataType::Sint32,u32_bytes(&[-5i32 as u32,300])));
+        assert_eq!(read(4,AttributeType::Color,&options).unwrap(), (DataType::Float32,fixture::f32_bytes(&[0.0,1.0])));
+        assert_eq!(read(5,AttributeType::Joints(0),&options).unwrap(), (DataType::Uint16x4,vec![1,0,2,0,3,0,4,0]));
+        assert!(matches!(read(6,AttributeType::Color,&options), Err(Error::UnsupportedDataTypeOrDimensions)));
+
+        // Integers are casted and normalized integers are converted to float, except joints
+        assert_eq!(read(0,AttributeType::Position,&dequantize).unwrap(),
+            (DataType::Float32x3,fixture::f32_bytes(&[1.0,2.0,3.0, 4.0,5.0,6.0])));
+        assert_eq!(read(1,AttributeType::Normal,&dequantize).unwrap(),
+            (DataType::Float32x3,fixture::f32_bytes(&[1.0,0.0,-1.0, 0.0,1.0,0.0])));
+        assert_eq!(read(2,AttributeType::Color,&dequantize).unwrap(), (DataType::Float32,fixture::f32_bytes(&[7.0,200.0])));
+        assert_eq!(read(5,AttributeType::Joints(0),&dequantize).unwrap(), (DataType::Uint16x4,vec![1,0,2,0,3,0,4,0]));
+    }
+}
@@ -1,43 +1,205 @@
 mod error;
+mod accessor;
+mod animation;
 mod buffer;
 mod image;
+mod material;
 mod mesh;
+mod node;
+mod options;
+mod skin;
 mod texture;
+mod uri;
+#[cfg(test)]
+mod fixture;
 
-use std::path::Path;
+use std::{path::Path, fs::File, io::BufReader};
 pub use error::Error;
-use gltf::{camera::Projection, Gltf};
+pub use options::LoadOptions;
+use gltf::{Gltf, Document};
+use animation::load_animations;
 use buffer::load_buffers;
 use image::load_images;
-use xecs::World;
-use crate::{Camera3D, Transform3D};
+use material::load_materials;
+use mesh::load_meshes;
+use node::load_nodes;
+use skin::load_skins;
+use texture::load_textures;
+use xecs::{World, EntityId};
 
-pub fn load_scene<P : AsRef<Path>>(world : &World,path : P) -> Result<(),Error> {
+/// Extensions handled by the loader rather than the gltf crate
+const SUPPORTED_EXTENSIONS: &[&str] = &["KHR_mesh_quantization"];
+
+/// Open and validate a glTF file
+fn open(path: &Path) -> Result<Gltf,Error> {
+    validate(Gltf::from_reader_without_validation(BufReader::new(File::open(path)?))?)
+}
+
+/// Validate a glTF file loaded without validation.
+/// Files requiring the extensions in `SUPPORTED_EXTENSIONS` are accepted
+fn validate(gltf: Gltf) -> Result<Gltf,Error> {
+    let mut json = gltf.document.into_json();
+    json.extensions_required.retain(|extension| !SUPPORTED_EXTENSIONS.contains(&extension.as_str()));
+    Ok(Gltf {
+        document: Document::from_json(json)?,
+        blob: gltf.blob,
+    })
+}
+
+/// Load the scene of a glTF file into `world`.
+/// Return the entities of root nodes
+pub fn load_scene<P : AsRef<Path>>(world : &World,path : P) -> Result<Vec<EntityId>,Error> {
+    load_scene_with_options(world,path,&LoadOptions::default())
+}
+
+/// Load the scene of a glTF file into `world` with optional steps.
+/// Return the entities of root nodes
+pub fn load_scene_with_options<P : AsRef<Path>>(world : &World,path : P,options : &LoadOptions) -> Result<Vec<EntityId>,Error> {
     let path = path.as_ref();
-    let gltf = Gltf::open(path)?;
-    let buffers = load_buffers(&gltf,&path)?;
-    let images = load_images(&gltf,&path,world)?;
+    let gltf = open(path)?;
+    load(world,&gltf,path,options)
+}
+
+/// Load the scene of an opened glTF file, uris are relative to `path`
+fn load(world: &World,gltf: &Gltf,path: &Path,options: &LoadOptions) -> Result<Vec<EntityId>,Error> {
+    let buffers = load_buffers(gltf,path)?;
+    let images = load_images(gltf,path,world,&buffers)?;
+    let textures = load_textures(gltf,world,&images)?;
+    let materials = load_materials(gltf,world,&textures)?;
     // load meshes
     // mapped gltf index to EntityId
-    // read node
-    for node in gltf.nodes() {
-        if let Some(mesh) = node.mesh() {
-        } else if let Some(camera) = node.camera() {
-            let camera = match camera.projection() {
-                Projection::Orthographic(_) => todo!(),
-                Projection::Perspective(perspective) => 
-                    Camera3D::perspective(
-                        perspective.aspect_ratio().unwrap(),
-                        perspective.yfov(),
-                        perspective.znear(),
-                        perspective.zfar().unwrap())
-            };
-            world.create_entity()
-                .attach(camera)
-                .attach(Transform3D::from_gltf_transform(node.transform()));
-        } else {
-            
+    let meshes = load_meshes(gltf,world,&buffers,&materials,options)?;
+    // The default scene is loaded, or the first scene if there is no default scene.
+    // All root nodes are loaded if there is no scene
+    let roots = if let Some(scene) = gltf.default_scene().or_else(|| gltf.scenes().next()) {
+        scene.nodes().collect::<Vec<_>>()
+    } else {
+        let mut is_root = vec![true;gltf.nodes().len()];
+        for child in gltf.nodes().flat_map(|node| node.children()) {
+            is_root[child.index()] = false;
         }
+        gltf.nodes()
+            .filter(|node| is_root[node.index()])
+            .collect::<Vec<_>>()
+    };
+    let nodes = load_nodes(gltf,world,roots.iter().cloned(),&meshes)?;
+    load_skins(gltf,world,&buffers,&nodes)?;
+    load_animations(gltf,world,&buffers,&nodes)?;
+    Ok(roots.iter()
+        .filter_map(|root| nodes[root.index()])
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use xecs::World;
+    use crate::{Mesh, Node, Material, Texture, image::ColorType};
+    use gltf::Gltf;
+    use crate::mesh::{Indices, AttributeType};
+    use super::{LoadOptions, Error, fixture, validate};
+
+    #[test]
+    fn required_extensions() {
+        let gltf = |extension: &str| Gltf::from_slice_without_validation(format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "extensionsUsed": ["{extension}"],
+            "extensionsRequired": ["{extension}"]
+        }}"#).as_bytes()).unwrap();
+        let validated = validate(gltf("KHR_mesh_quantization")).unwrap();
+        assert_eq!(validated.extensions_used().collect::<Vec<_>>(), vec!["KHR_mesh_quantization"]);
+        assert!(matches!(validate(gltf("EXT_unknown")), Err(Error::GltfError(_))));
+    }
+
+    #[test]
+    fn glb() {
+        let image = fixture::png(2,1,png::ColorType::Rgba,png::BitDepth::Eight,&[255,0,0,255, 0,255,0,255]);
+        let mut bin = fixture::f32_bytes(&[0.0,0.0,0.0, 1.0,0.0,0.0, 0.0,1.0,0.0]);
+        bin.extend([0,0, 1,0, 2,0, 0,0]);
+        bin.extend_from_slice(&image);
+        let json = format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "buffers": [{{ "byteLength": {} }}],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }},
+                {{ "buffer": 0, "byteOffset": 44, "byteLength": {} }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0,0,0], "max": [1,1,0] }},
+                {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+            ],
+            "images": [{{ "bufferView": 2, "mimeType": "image/png" }}],
+            "textures": [{{ "source": 0 }}],
+            "materials": [{{ "pbrMetallicRoughness": {{ "baseColorTexture": {{ "index": 0 }} }} }}],
+            "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0 }}] }}],
+            "nodes": [
+                {{ "name": "body", "children": [1], "translation": [1,0,0] }},
+                {{ "name": "wheel", "mesh": 0 }},
+                {{ "name": "unused" }}
+            ],
+            "scenes": [{{ "nodes": [2] }}, {{ "nodes": [0] }}],
+            "scene": 1
+        }}"#,bin.len(),image.len());
+        let mut world = World::new();
+        crate::init(&mut world);
+        let roots = fixture::load(&world,&fixture::glb(&json,&bin),&LoadOptions::default()).unwrap();
+        // Only the default scene is loaded
+        assert_eq!(roots.len(), 1);
+        assert_eq!(world.query::<&Node>().count(), 2);
+        let wheel = world.entity_component_read::<Node>(roots[0]).unwrap().children()[0];
+        assert_eq!(world.entity_component_read::<Node>(wheel).unwrap().parent(), Some(roots[0]));
+        let mesh = world.entity_component_read::<Mesh>(wheel).unwrap();
+        let mesh_data = mesh.mesh_data_read(&world);
+        assert_eq!(mesh_data.positions().unwrap().collect::<Vec<_>>(), vec![[0.0,0.0,0.0],[1.0,0.0,0.0],[0.0,1.0,0.0]]);
+        assert!(matches!(&mesh_data.indices, Some(Indices::U16(indices)) if indices == &[0,1,2]));
+        // The image in the BIN chunk is reached through the material
+        let material = world.entity_component_read::<Material>(mesh_data.material.unwrap()).unwrap();
+        let texture = material.pbr.base_color_texture.as_ref().unwrap().texture;
+        let texture = world.entity_component_read::<Texture>(texture).unwrap();
+        let image = texture.image_view(&world);
+        assert_eq!((image.width,image.height,image.color_type), (2,1,ColorType::RGBA));
+        assert_eq!(image.data, vec![255,0,0,255, 0,255,0,255]);
+    }
+
+    #[test]
+    fn interleaved() {
+        let mut buffer = fixture::f32_bytes(&[
+            0.0,0.0,0.0, 0.0,0.0,1.0,
+            1.0,0.0,0.0, 0.0,0.0,1.0,
+            0.0,1.0,0.0, 0.0,0.0,1.0,
+        ]);
+        buffer.extend(fixture::f32_bytes(&[0.0,0.0, 1.0,0.0, 0.0,1.0]));
+        let json = format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "buffers": [{{ "byteLength": 96, "uri": "{}" }}],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 72, "byteStride": 24 }},
+                {{ "buffer": 0, "byteOffset": 72, "byteLength": 24 }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0,0,0], "max": [1,1,0] }},
+                {{ "bufferView": 0, "byteOffset": 12, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC2" }}
+            ],
+            "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }} }}] }}],
+            "nodes": [
+                {{ "children": [1] }},
+                {{ "mesh": 0 }}
+            ]
+        }}"#,fixture::data_uri(&buffer));
+        let mut world = World::new();
+        crate::init(&mut world);
+        // All parentless nodes are roots when there is no scene
+        let roots = fixture::load(&world,json.as_bytes(),&LoadOptions::default()).unwrap();
+        assert_eq!(roots.len(), 1);
+        let child = world.entity_component_read::<Node>(roots[0]).unwrap().children()[0];
+        let mesh = world.entity_component_read::<Mesh>(child).unwrap();
+        let mesh_data = mesh.mesh_data_read(&world);
+        // The separate texcoords are packed into the interleaved layout
+        assert!(mesh_data.vertices.is_interleaved());
+        assert_eq!(mesh_data.positions().unwrap().collect::<Vec<_>>(), vec![[0.0,0.0,0.0],[1.0,0.0,0.0],[0.0,1.0,0.0]]);
+        assert_eq!(mesh_data.normals().unwrap().collect::<Vec<_>>(), vec![[0.0,0.0,1.0];3]);
+        assert_eq!(mesh_data.tex_coords(0).unwrap().collect::<Vec<_>>(), vec![[0.0,0.0],[1.0,0.0],[0.0,1.0]]);
+        assert!(mesh_data.attribute_info(AttributeType::Tangent).is_none());
     }
-    Ok(())
 }
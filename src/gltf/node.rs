@@ -0,0 +1,241 @@
+use std::collections::HashSet;
+use gltf::{Gltf, camera::Projection, khr_lights_punctual::Kind};
+use xecs::{World, EntityId};
+use crate::{camera, Camera3D, Transform3D, Mesh, Node, mesh::{MeshData, MorphWeights}, DirectionalLight, PointLight, SpotLight};
+use super::Error;
+
+/// Create entities for `roots` and all their descendants.
+/// Return the entity of every glTF node, None if the node is not in the tree
+pub fn load_nodes<'a>(gltf: &Gltf,world: &World,roots: impl Iterator<Item = gltf::Node<'a>>,meshes: &[Vec<EntityId>]) -> Result<Vec<Option<EntityId>>,Error> {
+    let mut nodes = vec![None;gltf.nodes().len()];
+    for root in roots {
+        load_node(world,root,None,meshes,&mut nodes)?;
+    }
+    Ok(nodes)
+}
+
+fn load_node(world: &World,node: gltf::Node,parent: Option<EntityId>,meshes: &[Vec<EntityId>],nodes: &mut [Option<EntityId>]) -> Result<EntityId,Error> {
+    let id = world.create_entity()
+        .attach(Transform3D::from_gltf_transform(node.transform()))
+        .into_id();
+    nodes[node.index()] = Some(id);
+    let mut node_component = match parent {
+        Some(parent) => Node::from_parent(parent),
+        None => Node::new(),
+    };
+    if let Some(mesh) = node.mesh() {
+        // The first primitive is attached to the node,
+        // other primitives become children with identity transform
+        let mut primitives = meshes[mesh.index()].iter();
+        if let Some(&mesh_data_id) = primitives.next() {
+            world.attach_component(id,Mesh::from_data(mesh_data_id));
+            attach_morph_weights(world,id,mesh_data_id,node.weights());
+        }
+        for &mesh_data_id in primitives {
+            let child = world.create_entity()
+                .attach(Mesh::from_data(mesh_data_id))
+                .attach(Transform3D::new())
+                .attach(Node::from_parent(id))
+                .into_id();
+            attach_morph_weights(world,child,mesh_data_id,node.weights());
+            node_component.add_child(child);
+        }
+    }
+    if let Some(camera) = node.camera() {
+        let camera = match camera.projection() {
+            Projection::Orthographic(orthographic) =>
+                Camera3D::ortho(
+                    -orthographic.xmag(),
+                    orthographic.xmag(),
+                    -orthographic.ymag(),
+                    orthographic.ymag(),
+                    orthographic.znear(),
+                    orthographic.zfar()),
+            // aspect ratio is resolved from viewport if it's None
+            Projection::Perspective(perspective) =>
+                Camera3D::new(camera::Projection::Perspective {
+                    aspect: perspective.aspect_ratio(),
+                    fov_y: perspective.yfov(),
+                    near: perspective.znear(),
+                    far: perspective.zfar(),
+                })
+        };
+        world.attach_component(id,camera);
+    }
+    if let Some(light) = node.light() {
+        match light.kind() {
+            Kind::Directional =>
+                world.attach_component(id,DirectionalLight::new(light.color(),light.intensity())),
+            Kind::Point =>
+                world.attach_component(id,PointLight::new(light.color(),light.intensity(),light.range())),
+            Kind::Spot { inner_cone_angle, outer_cone_angle } =>
+                world.attach_component(id,SpotLight::new(
+                    light.color(),
+                    light.intensity(),
+                    light.range(),
+                    inner_cone_angle,
+                    outer_cone_angle)),
+        };
+    }
+    for child in node.children() {
+        let child = load_node(world,child,Some(id),meshes,nodes)?;
+        node_component.add_child(child);
+    }
+    world.attach_component(id,node_component);
+    Ok(id)
+}
+
+/// Attach MorphWeights if the mesh has morph targets.
+/// The weights of node override the default weights of mesh
+fn attach_morph_weights(world: &World,id: EntityId,mesh_data_id: EntityId,weights: Option<&[f32]>) {
+    let mesh_data = world.entity_component_read::<MeshData>(mesh_data_id).unwrap();
+    if mesh_data.morph_targets.is_empty() {
+        return;
+    }
+    let mut weights = weights
+        .map(Vec::from)
+        .unwrap_or_else(|| mesh_data.morph_weights.clone());
+    weights.resize(mesh_data.morph_targets.len(),0.0);
+    drop(mesh_data);
+    world.attach_component(id,MorphWeights(weights));
+}
+
+/// Get the entities with Mesh of a glTF node.
+/// The extra primitives of the mesh are children which are not glTF nodes
+pub fn mesh_entities(world: &World,id: EntityId,node_entities: &HashSet<EntityId>) -> Vec<EntityId> {
+    let mut entities = vec![id];
+    if let Some(node) = world.entity_component_read::<Node>(id) {
+        entities.extend(node.children()
+            .iter()
+            .filter(|child| !node_entities.contains(child)));
+    }
+    entities.retain(|entity| world.entity_component_read::<Mesh>(*entity).is_some());
+    entities
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+    use xecs::World;
+    use crate::{Camera3D, Mesh, MorphWeights, Node, DirectionalLight, PointLight, SpotLight, camera::Projection};
+    use crate::gltf::{LoadOptions, fixture};
+
+    #[test]
+    fn cameras() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "cameras": [
+                { "type": "orthographic", "orthographic": { "xmag": 2.0, "ymag": 1.0, "znear": 0.1, "zfar": 10.0 } },
+                { "type": "perspective", "perspective": { "aspectRatio": 2.0, "yfov": 1.0, "znear": 0.1 } },
+                { "type": "perspective", "perspective": { "yfov": 1.0, "znear": 0.1, "zfar": 100.0 } }
+            ],
+            "nodes": [
+                { "camera": 0 },
+                { "camera": 1 },
+                { "camera": 2 }
+            ]
+        }"#;
+        let mut world = World::new();
+        crate::init(&mut world);
+        let roots = fixture::load(&world,json.as_bytes(),&LoadOptions::default()).unwrap();
+        let orthographic = world.entity_component_read::<Camera3D>(roots[0]).unwrap();
+        assert_eq!(*orthographic.projection(), Projection::Orthographic {
+            left: -2.0,
+            right: 2.0,
+            bottom: -1.0,
+            top: 1.0,
+            near: 0.1,
+            far: 10.0,
+        });
+        assert_eq!(*orthographic.projection_matrix(), glm::ortho(-2.0,2.0,-1.0,1.0,0.1,10.0));
+        // A missing zfar gives an infinite projection
+        let infinite = world.entity_component_read::<Camera3D>(roots[1]).unwrap();
+        assert_eq!(*infinite.projection(), Projection::Perspective { aspect: Some(2.0), fov_y: 1.0, near: 0.1, far: None });
+        assert_eq!(*infinite.projection_matrix(), glm::infinite_perspective_rh_no(2.0,1.0,0.1));
+        drop(orthographic);
+        drop(infinite);
+        // A missing aspect ratio is resolved from the viewport
+        let mut perspective = world.entity_component_write::<Camera3D>(roots[2]).unwrap();
+        assert_eq!(*perspective.projection(), Projection::Perspective { aspect: None, fov_y: 1.0, near: 0.1, far: Some(100.0) });
+        perspective.set_viewport(1920.0,1080.0);
+        assert_eq!(*perspective.projection_matrix(), glm::perspective(1920.0 / 1080.0,1.0,0.1,100.0));
+    }
+
+    #[test]
+    fn lights() {
+        let json = r#"{
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": { "KHR_lights_punctual": { "lights": [
+                { "type": "directional", "color": [1.0, 0.5, 0.25], "intensity": 3.0 },
+                { "type": "point", "range": 10.0 },
+                { "type": "spot", "intensity": 2.0, "spot": { "innerConeAngle": 0.25, "outerConeAngle": 0.5 } }
+            ] } },
+            "nodes": [
+                { "extensions": { "KHR_lights_punctual": { "light": 0 } } },
+                { "extensions": { "KHR_lights_punctual": { "light": 1 } } },
+                { "extensions": { "KHR_lights_punctual": { "light": 2 } } }
+            ]
+        }"#;
+        let mut world = World::new();
+        crate::init(&mut world);
+        let roots = fixture::load(&world,json.as_bytes(),&LoadOptions::default()).unwrap();
+        assert_eq!(*world.entity_component_read::<DirectionalLight>(roots[0]).unwrap(), DirectionalLight::new([1.0,0.5,0.25],3.0));
+        // Missing color and intensity fall back to white and 1
+        assert_eq!(*world.entity_component_read::<PointLight>(roots[1]).unwrap(), PointLight::new([1.0,1.0,1.0],1.0,Some(10.0)));
+        assert_eq!(*world.entity_component_read::<SpotLight>(roots[2]).unwrap(), SpotLight::new([1.0,1.0,1.0],2.0,None,0.25,0.5));
+        assert!(world.entity_component_read::<PointLight>(roots[0]).is_none());
+    }
+
+    #[test]
+    fn morph_weights() {
+        let mut buffer = fixture::f32_bytes(&[0.0,0.0,0.0, 1.0,0.0,0.0, 0.0,1.0,0.0]);
+        buffer.extend(fixture::f32_bytes(&[0.0,0.0,1.0, 0.0,0.0,1.0, 0.0,0.0,1.0]));
+        buffer.extend([2,0,0,0]);
+        buffer.extend(fixture::f32_bytes(&[1.0,1.0,1.0]));
+        let primitive = r#"{ "attributes": { "POSITION": 0 }, "targets": [{ "POSITION": 1 }, { "POSITION": 2 }] }"#;
+        let json = format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "buffers": [{{ "byteLength": {}, "uri": "{}" }}],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                {{ "buffer": 0, "byteOffset": 36, "byteLength": 36 }},
+                {{ "buffer": 0, "byteOffset": 72, "byteLength": 1 }},
+                {{ "buffer": 0, "byteOffset": 76, "byteLength": 12 }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0,0,0], "max": [1,1,0] }},
+                {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                {{ "componentType": 5126, "count": 3, "type": "VEC3", "sparse": {{
+                    "count": 1,
+                    "indices": {{ "bufferView": 2, "componentType": 5121 }},
+                    "values": {{ "bufferView": 3 }}
+                }} }}
+            ],
+            "meshes": [{{ "primitives": [{primitive}, {primitive}], "weights": [0.1, 0.2] }}],
+            "nodes": [
+                {{ "mesh": 0, "weights": [0.5, 0.75] }},
+                {{ "mesh": 0 }}
+            ]
+        }}"#,buffer.len(),fixture::data_uri(&buffer));
+        let mut world = World::new();
+        crate::init(&mut world);
+        let roots = fixture::load(&world,json.as_bytes(),&LoadOptions::default()).unwrap();
+        let weights = |id| world.entity_component_read::<MorphWeights>(id).unwrap().0.clone();
+        // Node weights take precedence over the default weights of mesh
+        assert_eq!(weights(roots[0]), vec![0.5,0.75]);
+        assert_eq!(weights(roots[1]), vec![0.1,0.2]);
+        // The extra primitive gets the weights of its node
+        let primitive = world.entity_component_read::<Node>(roots[0]).unwrap().children()[0];
+        assert_eq!(weights(primitive), vec![0.5,0.75]);
+
+        let mesh = world.entity_component_read::<Mesh>(roots[0]).unwrap();
+        let mesh_data = mesh.mesh_data_read(&world);
+        assert_eq!(mesh_data.morph_weights, vec![0.1,0.2]);
+        assert_eq!(mesh_data.morph_targets[0].positions, Some(vec![[0.0,0.0,1.0];3]));
+        assert_eq!(mesh_data.morph_targets[1].positions, Some(vec![[0.0,0.0,0.0],[0.0,0.0,0.0],[1.0,1.0,1.0]]));
+        assert_eq!(mesh_data.morph_targets[1].normals, None);
+        let morphed = mesh_data.morph(&weights(roots[0]));
+        assert_eq!(morphed.positions().unwrap().nth(2), Some([0.75,1.75,1.25]));
+    }
+}
@@ -0,0 +1,27 @@
+/// Optional steps of loading glTF files
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct LoadOptions {
+    /// Generate MikkTSpace tangents for triangle meshes without TANGENT
+    pub generate_tangents: bool,
+    /// Generate flat normals for triangle meshes without NORMAL, as the glTF spec recommends
+    pub generate_normals: bool,
+    /// Convert quantized vertex attributes except joints to float,
+    /// otherwise they are kept as integer types and only widened when there is no matching `DataType`
+    pub dequantize: bool,
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        LoadOptions {
+            generate_tangents: false,
+            generate_normals: false,
+            dequantize: false,
+        }
+    }
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions::new()
+    }
+}
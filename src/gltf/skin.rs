@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use nalgebra_glm as glm;
+use gltf::Gltf;
+use xecs::{World, EntityId};
+use crate::Skin;
+use super::{Error, accessor, node::mesh_entities};
+
+/// Attach Skin to the mesh entities of skinned nodes.
+/// `nodes` is the entity of every glTF node
+pub fn load_skins(gltf: &Gltf,world: &World,buffers: &[Vec<u8>],nodes: &[Option<EntityId>]) -> Result<(),Error> {
+    let node_entities = nodes.iter()
+        .flatten()
+        .copied()
+        .collect::<HashSet<_>>();
+    for node in gltf.nodes() {
+        let (Some(skin),Some(id)) = (node.skin(),nodes[node.index()]) else {
+            continue;
+        };
+        let inverse_bind_matrices = if let Some(accessor) = skin.inverse_bind_matrices() {
+            accessor::read(&accessor,buffers)?
+                .chunks_exact(16 * 4)
+                .map(|matrix| {
+                    let matrix = matrix.chunks_exact(4)
+                        .map(|bytes| f32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]))
+                        .collect::<Vec<_>>();
+                    glm::make_mat4(&matrix)
+                })
+                .collect()
+        } else {
+            vec![glm::identity();skin.joints().len()]
+        };
+        let joints = skin.joints()
+            .map(|joint| nodes[joint.index()].ok_or(Error::MissingJoint))
+            .collect::<Result<Vec<_>,_>>()?;
+        let mut component = Skin::new(joints,inverse_bind_matrices);
+        component.skeleton = skin.skeleton()
+            .and_then(|skeleton| nodes[skeleton.index()]);
+        for mesh in mesh_entities(world,id,&node_entities) {
+            world.attach_component(mesh,component.clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+    use xecs::World;
+    use crate::{Skin, Node, Mesh};
+    use crate::gltf::{LoadOptions, fixture};
+
+    #[test]
+    fn import() {
+        let mut buffer = fixture::f32_bytes(&[0.0,0.0,0.0, 1.0,0.0,0.0, 0.0,1.0,0.0]);
+        buffer.extend([0,0,0,0, 0,1,0,0, 1,0,0,0]);
+        buffer.extend(fixture::f32_bytes(&[1.0,0.0,0.0,0.0, 0.5,0.5,0.0,0.0, 1.0,0.0,0.0,0.0]));
+        let inverse_binds = [glm::translation(&glm::vec3(0.0,-1.0,0.0)),glm::translation(&glm::vec3(0.0,-2.0,0.0))];
+        for matrix in &inverse_binds {
+            buffer.extend(fixture::f32_bytes(matrix.as_slice()));
+        }
+        let json = format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "buffers": [{{ "byteLength": {}, "uri": "{}" }}],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                {{ "buffer": 0, "byteOffset": 36, "byteLength": 12 }},
+                {{ "buffer": 0, "byteOffset": 48, "byteLength": 48 }},
+                {{ "buffer": 0, "byteOffset": 96, "byteLength": 128 }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0,0,0], "max": [1,1,0] }},
+                {{ "bufferView": 1, "componentType": 5121, "count": 3, "type": "VEC4" }},
+                {{ "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC4" }},
+                {{ "bufferView": 3, "componentType": 5126, "count": 2, "type": "MAT4" }}
+            ],
+            "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0, "JOINTS_0": 1, "WEIGHTS_0": 2 }} }}] }}],
+            "skins": [{{ "joints": [1, 2], "inverseBindMatrices": 3, "skeleton": 1 }}],
+            "nodes": [
+                {{ "mesh": 0, "skin": 0 }},
+                {{ "translation": [0,1,0], "children": [2] }},
+                {{ "translation": [0,1,0] }}
+            ],
+            "scenes": [{{ "nodes": [0, 1] }}]
+        }}"#,buffer.len(),fixture::data_uri(&buffer));
+        let mut world = World::new();
+        crate::init(&mut world);
+        let roots = fixture::load(&world,json.as_bytes(),&LoadOptions::default()).unwrap();
+        let root_joint = roots[1];
+        let child_joint = world.entity_component_read::<Node>(root_joint).unwrap().children()[0];
+        let skin = world.entity_component_read::<Skin>(roots[0]).unwrap();
+        assert_eq!(skin.joints, vec![root_joint,child_joint]);
+        assert_eq!(skin.inverse_bind_matrices, inverse_binds.to_vec());
+        assert_eq!(skin.skeleton, Some(root_joint));
+        let mesh = world.entity_component_read::<Mesh>(roots[0]).unwrap();
+        let mesh_data = mesh.mesh_data_read(&world);
+        assert_eq!(mesh_data.joints(0).unwrap().collect::<Vec<_>>(), vec![[0,0,0,0],[0,1,0,0],[1,0,0,0]]);
+        assert_eq!(mesh_data.weights(0).unwrap().nth(1), Some([0.5,0.5,0.0,0.0]));
+    }
+}
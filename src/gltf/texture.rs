@@ -1,17 +1,84 @@
-use gltf::Gltf;
+use gltf::{Gltf, texture};
 use xecs::{World, EntityId};
+use crate::texture::{Texture, MagFilter, MinFilter, Wrap};
 use super::Error;
 
 pub fn load_textures(gltf : &Gltf,world : &World,images : &[EntityId]) -> Result<Vec<EntityId>,Error> {
     let mut textures = Vec::new();
     for texture in gltf.textures() {
         let image_id = images[texture.source().index()];
-        if let Some(sample_id) = texture.sampler().index() {
-            
-        } else {
-            // default sampler
-            
-        }
+        // The default sampler is used if texture has no sampler,
+        // missing fields fall back to Linear and Repeat
+        let sampler = texture.sampler();
+        let texture = Texture {
+            image: image_id,
+            view: [0.0, 0.0, 1.0, 1.0],
+            mag_filter: match sampler.mag_filter() {
+                Some(texture::MagFilter::Nearest) => MagFilter::Nearest,
+                Some(texture::MagFilter::Linear) | None => MagFilter::Linear,
+            },
+            min_filter: match sampler.min_filter() {
+                Some(texture::MinFilter::Nearest) => MinFilter::Nearest,
+                Some(texture::MinFilter::Linear) | None => MinFilter::Linear,
+                Some(texture::MinFilter::NearestMipmapNearest) => MinFilter::NearestMipmapNearest,
+                Some(texture::MinFilter::LinearMipmapNearest) => MinFilter::LinearMipmapNearest,
+                Some(texture::MinFilter::NearestMipmapLinear) => MinFilter::NearestMipmapLinear,
+                Some(texture::MinFilter::LinearMipmapLinear) => MinFilter::LinearMipmapLinear,
+            },
+            wrap_s: wrap(sampler.wrap_s()),
+            wrap_t: wrap(sampler.wrap_t()),
+        };
+        let id = world.create_entity()
+            .attach(texture)
+            .into_id();
+        textures.push(id);
     }
     Ok(textures)
-}
\ No newline at end of file
+}
+
+fn wrap(mode: texture::WrappingMode) -> Wrap {
+    match mode {
+        texture::WrappingMode::ClampToEdge => Wrap::ClampToEdge,
+        texture::WrappingMode::MirroredRepeat => Wrap::MirroredRepeat,
+        texture::WrappingMode::Repeat => Wrap::Repeat,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gltf::Gltf;
+    use xecs::World;
+    use crate::texture::{Texture, MagFilter, MinFilter, Wrap};
+    use super::load_textures;
+
+    #[test]
+    fn samplers() {
+        let gltf = Gltf::from_slice(br#"{
+            "asset": { "version": "2.0" },
+            "images": [{ "uri": "unused.png" }],
+            "samplers": [
+                { "magFilter": 9728, "minFilter": 9987, "wrapS": 33071, "wrapT": 33648 },
+                { "minFilter": 9728 }
+            ],
+            "textures": [
+                { "source": 0 },
+                { "source": 0, "sampler": 0 },
+                { "source": 0, "sampler": 1 }
+            ]
+        }"#).unwrap();
+        let mut world = World::new();
+        crate::init(&mut world);
+        let image = world.create_entity().into_id();
+        let textures = load_textures(&gltf,&world,&[image]).unwrap();
+        let sampler = |index: usize| {
+            let texture = world.entity_component_read::<Texture>(textures[index]).unwrap();
+            assert_eq!(texture.image, image);
+            (texture.mag_filter,texture.min_filter,texture.wrap_s,texture.wrap_t)
+        };
+        // Without sampler
+        assert_eq!(sampler(0), (MagFilter::Linear,MinFilter::Linear,Wrap::Repeat,Wrap::Repeat));
+        assert_eq!(sampler(1), (MagFilter::Nearest,MinFilter::LinearMipmapLinear,Wrap::ClampToEdge,Wrap::MirroredRepeat));
+        // Missing fields fall back to the defaults
+        assert_eq!(sampler(2), (MagFilter::Linear,MinFilter::Nearest,Wrap::Repeat,Wrap::Repeat));
+    }
+}
@@ -0,0 +1,59 @@
+use std::{path::Path, borrow::Cow, fs::File, io::Read};
+use super::Error;
+
+/// Read the content of an uri.
+/// The uri can be a base64 data uri or a path relative to `gltf_path`
+pub fn read(uri: &str,gltf_path: &Path) -> Result<Vec<u8>,Error> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (header,data) = data.split_once(',')
+            .ok_or(Error::UnsupportedUri)?;
+        if !header.ends_with(";base64") {
+            return Err(Error::UnsupportedUri);
+        }
+        Ok(base64::decode(data)?)
+    } else {
+        let mut path = Cow::Borrowed(gltf_path);
+        let file_path = Path::new(uri);
+        let file_path = if file_path.is_relative() {
+            path.to_mut().pop();
+            path.to_mut().push(file_path);
+            path.as_ref()
+        } else {
+            file_path
+        };
+        let mut file = File::open(file_path)?;
+        let mut v = Vec::new();
+        file.read_to_end(&mut v)?;
+        Ok(v)
+    }
+}
+
+/// Get the MIME type of a data uri.
+/// Return None if the uri is not a data uri or no MIME type is given
+pub fn mime_type(uri: &str) -> Option<&str> {
+    let header = uri.strip_prefix("data:")?
+        .split_once(',')?
+        .0;
+    let mime_type = header.split(';').next()?;
+    if mime_type.is_empty() {
+        None
+    } else {
+        Some(mime_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::{read, mime_type};
+
+    #[test]
+    fn data_uri() {
+        let uri = "data:application/octet-stream;base64,AAECAw==";
+        assert_eq!(mime_type(uri), Some("application/octet-stream"));
+        assert_eq!(read(uri,Path::new("")).unwrap(), vec![0,1,2,3]);
+        assert_eq!(mime_type("data:;base64,AAECAw=="), None);
+        assert_eq!(mime_type("textures/cube.png"), None);
+        assert!(read("data:text/plain,abc",Path::new("")).is_err());
+    }
+}
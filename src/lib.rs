@@ -1,22 +1,51 @@
+pub mod animation;
 pub mod material;
 pub mod mesh;
 mod transform;
-mod camera;
+pub mod camera;
 mod light;
 mod node;
+mod skin;
 mod texture;
 mod image;
+mod bounds;
 pub mod gltf;
 
 pub use transform::{
     Transform2D,
-    Transform3D
+    Transform3D,
+    GlobalTransform3D,
+    propagate_transforms
 };
 pub use camera::{
     Camera2D,
-    Camera3D
+    Camera3D,
+    Frustum
 };
-pub use mesh::Mesh;
+pub use light::{
+    DirectionalLight,
+    PointLight,
+    SpotLight
+};
+pub use animation::{
+    AnimationClip,
+    AnimationPlayer,
+    play_animations
+};
+pub use mesh::{
+    Mesh,
+    MorphWeights
+};
+pub use bounds::{
+    Aabb,
+    Sphere,
+    Bounds,
+    update_bounds,
+    subtree_bounds
+};
+pub use node::Node;
+pub use skin::Skin;
+pub use material::Material;
 pub use texture::Texture;
 pub use image::Image;
 
@@ -25,8 +54,21 @@ use xecs::World;
 pub fn init(world : &mut World) {
     world.register::<Transform2D>()
         .register::<Transform3D>()
+        .register::<GlobalTransform3D>()
         .register::<Camera2D>()
         .register::<Camera3D>()
         .register::<mesh::MeshData>()
-        .register::<Mesh>();
+        .register::<Mesh>()
+        .register::<MorphWeights>()
+        .register::<Node>()
+        .register::<Skin>()
+        .register::<AnimationClip>()
+        .register::<AnimationPlayer>()
+        .register::<Image>()
+        .register::<Texture>()
+        .register::<Material>()
+        .register::<DirectionalLight>()
+        .register::<PointLight>()
+        .register::<SpotLight>()
+        .register::<Bounds>();
 }
@@ -0,0 +1,65 @@
+/// A light infinitely far away, emitting along -Z of its Transform3D
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct DirectionalLight {
+    /// Linear RGB color
+    pub color: [f32; 3],
+    /// Illuminance in lux
+    pub intensity: f32,
+}
+
+/// A light emitting in all directions from the position of its Transform3D
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct PointLight {
+    /// Linear RGB color
+    pub color: [f32; 3],
+    /// Luminous intensity in candela
+    pub intensity: f32,
+    /// The distance where the light intensity reaches zero, None means infinite
+    pub range: Option<f32>,
+}
+
+/// A light emitting in a cone along -Z of its Transform3D
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct SpotLight {
+    /// Linear RGB color
+    pub color: [f32; 3],
+    /// Luminous intensity in candela
+    pub intensity: f32,
+    /// The distance where the light intensity reaches zero, None means infinite
+    pub range: Option<f32>,
+    /// Angle in radians from the center where falloff begins
+    pub inner_cone_angle: f32,
+    /// Angle in radians from the center where falloff ends
+    pub outer_cone_angle: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(color: [f32; 3],intensity: f32) -> Self {
+        DirectionalLight {
+            color,
+            intensity,
+        }
+    }
+}
+
+impl PointLight {
+    pub fn new(color: [f32; 3],intensity: f32,range: Option<f32>) -> Self {
+        PointLight {
+            color,
+            intensity,
+            range,
+        }
+    }
+}
+
+impl SpotLight {
+    pub fn new(color: [f32; 3],intensity: f32,range: Option<f32>,inner_cone_angle: f32,outer_cone_angle: f32) -> Self {
+        SpotLight {
+            color,
+            intensity,
+            range,
+            inner_cone_angle,
+            outer_cone_angle,
+        }
+    }
+}
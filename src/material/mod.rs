@@ -1,7 +1,86 @@
+mod pbr;
+
 use xecs::EntityId;
 
+pub use pbr::PbrMetallicRoughness;
+
+/// The material component with glTF metallic-roughness PBR model
+#[derive(Debug,Clone,PartialEq)]
 pub struct Material {
-    alpha: f32,
-    double_sided: bool,
-    color_texture: Option<EntityId>,
-}
\ No newline at end of file
+    /// Metallic-roughness parameters
+    pub pbr: PbrMetallicRoughness,
+    /// Tangent space normal map
+    pub normal_texture: Option<NormalTexture>,
+    /// Ambient occlusion map, sampled from R channel
+    pub occlusion_texture: Option<OcclusionTexture>,
+    /// Emissive map, sampled from RGB channels
+    pub emissive_texture: Option<TextureInfo>,
+    /// Linear emissive color multiplied with emissive map
+    pub emissive_factor: [f32; 3],
+    pub alpha_mode: AlphaMode,
+    /// Alpha threshold, only used in `AlphaMode::Mask`
+    pub alpha_cutoff: f32,
+    /// Disable back-face culling if true
+    pub double_sided: bool,
+}
+
+impl Material {
+    /// Create a material with the default values in glTF spec
+    pub fn new() -> Self {
+        Material {
+            pbr: PbrMetallicRoughness::new(),
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+            emissive_factor: [0.0, 0.0, 0.0],
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material::new()
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum AlphaMode {
+    /// Alpha value is ignored
+    Opaque,
+    /// Fragment is discarded if alpha is less than `alpha_cutoff`
+    Mask,
+    /// Alpha blending
+    Blend,
+}
+
+/// Reference to a texture
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct TextureInfo {
+    /// The texture entity
+    pub texture: EntityId,
+    /// The set index of `AttributeType::TexCoord`
+    pub tex_coord: u32,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct NormalTexture {
+    /// The texture entity
+    pub texture: EntityId,
+    /// The set index of `AttributeType::TexCoord`
+    pub tex_coord: u32,
+    /// The scale applied to X and Y of each sampled normal
+    pub scale: f32,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct OcclusionTexture {
+    /// The texture entity
+    pub texture: EntityId,
+    /// The set index of `AttributeType::TexCoord`
+    pub tex_coord: u32,
+    /// Occlusion = 1.0 + strength * (sampled - 1.0)
+    pub strength: f32,
+}
@@ -0,0 +1,33 @@
+use super::TextureInfo;
+
+/// Parameters of metallic-roughness model
+#[derive(Debug,Clone,PartialEq)]
+pub struct PbrMetallicRoughness {
+    /// Linear RGBA multiplied with base color texture
+    pub base_color_factor: [f32; 4],
+    pub base_color_texture: Option<TextureInfo>,
+    /// Multiplied with B channel of metallic roughness texture
+    pub metallic_factor: f32,
+    /// Multiplied with G channel of metallic roughness texture
+    pub roughness_factor: f32,
+    pub metallic_roughness_texture: Option<TextureInfo>,
+}
+
+impl PbrMetallicRoughness {
+    /// Create parameters with the default values in glTF spec
+    pub fn new() -> Self {
+        PbrMetallicRoughness {
+            base_color_factor: [1.0, 1.0, 1.0, 1.0],
+            base_color_texture: None,
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            metallic_roughness_texture: None,
+        }
+    }
+}
+
+impl Default for PbrMetallicRoughness {
+    fn default() -> Self {
+        PbrMetallicRoughness::new()
+    }
+}
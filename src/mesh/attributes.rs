@@ -4,8 +4,14 @@ use super::DataType;
 pub enum AttributeType{
     Position,
     Normal,
+    /// XYZ is the tangent, W is the handedness of bitangent
+    Tangent,
     Color,
     TexCoord(u32),
+    /// Indices of joints in Skin
+    Joints(u32),
+    /// Weights of joints
+    Weights(u32),
 }
 
 #[derive(Debug,Clone)]
@@ -17,6 +23,7 @@ pub struct Attribute {
     /// The number of elements in the attribute
     /// Attribute Size = Data Type Size * Number of Elements
     pub count: usize,
-    /// The offset of the attribute in bytes
+    /// The offset of the attribute in bytes.
+    /// In interleaved vertices, it's the offset inside a vertex.
     pub offset: usize
 }
@@ -0,0 +1,20 @@
+use nalgebra_glm as glm;
+use crate::bounds::{Aabb, Sphere};
+use super::MeshData;
+
+impl MeshData {
+    /// The bounding box of positions.
+    /// `bounds` is used if it's given, for example by the min and max of glTF accessors
+    pub fn aabb(&self) -> Option<Aabb> {
+        self.bounds.or_else(|| Aabb::from_points(self.positions()?))
+    }
+
+    /// The bounding sphere of positions, centered at the center of [aabb](MeshData::aabb)
+    pub fn bounding_sphere(&self) -> Option<Sphere> {
+        let center = self.aabb()?.center();
+        let radius = self.positions()?
+            .map(|position| glm::distance(&center,&glm::make_vec3(&position)))
+            .fold(0.0,f32::max);
+        Some(Sphere::new(center,radius))
+    }
+}
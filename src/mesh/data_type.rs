@@ -104,6 +104,104 @@ impl DataType {
         }
     }
 
+    /// Get the normalized variant of an integer type.
+    /// Return itself if there is no normalized variant
+    pub const fn to_normalized(&self) -> Self {
+        match self {
+            Self::Uint8x2 => Self::Unorm8x2,
+            Self::Uint8x4 => Self::Unorm8x4,
+            Self::Sint8x2 => Self::Snorm8x2,
+            Self::Sint8x4 => Self::Snorm8x4,
+            Self::Uint16x2 => Self::Unorm16x2,
+            Self::Uint16x4 => Self::Unorm16x4,
+            Self::Sint16x2 => Self::Snorm16x2,
+            Self::Sint16x4 => Self::Snorm16x4,
+            _ => *self,
+        }
+    }
+
+    /// Returns the number of components.
+    pub const fn components(&self) -> usize {
+        match self {
+            Self::Float32 | Self::Uint32 | Self::Sint32 | Self::Float64 => 1,
+            Self::Uint8x2
+            | Self::Sint8x2
+            | Self::Unorm8x2
+            | Self::Snorm8x2
+            | Self::Uint16x2
+            | Self::Sint16x2
+            | Self::Unorm16x2
+            | Self::Snorm16x2
+            | Self::Float16x2
+            | Self::Float32x2
+            | Self::Uint32x2
+            | Self::Sint32x2
+            | Self::Float64x2 => 2,
+            Self::Float32x3 | Self::Uint32x3 | Self::Sint32x3 | Self::Float64x3 => 3,
+            Self::Uint8x4
+            | Self::Sint8x4
+            | Self::Unorm8x4
+            | Self::Snorm8x4
+            | Self::Uint16x4
+            | Self::Sint16x4
+            | Self::Unorm16x4
+            | Self::Snorm16x4
+            | Self::Float16x4
+            | Self::Float32x4
+            | Self::Uint32x4
+            | Self::Sint32x4
+            | Self::Float64x4 => 4,
+        }
+    }
+
+    pub const fn is_unsigned_integer(&self) -> bool {
+        matches!(self,
+            Self::Uint8x2 | Self::Uint8x4 | Self::Uint16x2 | Self::Uint16x4 |
+            Self::Uint32 | Self::Uint32x2 | Self::Uint32x3 | Self::Uint32x4)
+    }
+
+    /// Decode the `index`th component of an element in `bytes`.
+    /// Normalized integers are converted to float [0, 1] or [-1, 1], other integers are casted.
+    pub fn component_f32(&self,bytes: &[u8],index: usize) -> f32 {
+        let size = self.size() as usize / self.components();
+        let bytes = &bytes[index * size..(index + 1) * size];
+        match self {
+            Self::Uint8x2 | Self::Uint8x4 => bytes[0] as f32,
+            Self::Sint8x2 | Self::Sint8x4 => bytes[0] as i8 as f32,
+            Self::Unorm8x2 | Self::Unorm8x4 => bytes[0] as f32 / u8::MAX as f32,
+            Self::Snorm8x2 | Self::Snorm8x4 => (bytes[0] as i8 as f32 / i8::MAX as f32).max(-1.0),
+            Self::Uint16x2 | Self::Uint16x4 => u16::from_le_bytes([bytes[0],bytes[1]]) as f32,
+            Self::Sint16x2 | Self::Sint16x4 => i16::from_le_bytes([bytes[0],bytes[1]]) as f32,
+            Self::Unorm16x2 | Self::Unorm16x4 =>
+                u16::from_le_bytes([bytes[0],bytes[1]]) as f32 / u16::MAX as f32,
+            Self::Snorm16x2 | Self::Snorm16x4 =>
+                (i16::from_le_bytes([bytes[0],bytes[1]]) as f32 / i16::MAX as f32).max(-1.0),
+            Self::Float16x2 | Self::Float16x4 => f16_to_f32(u16::from_le_bytes([bytes[0],bytes[1]])),
+            Self::Float32 | Self::Float32x2 | Self::Float32x3 | Self::Float32x4 =>
+                f32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]),
+            Self::Uint32 | Self::Uint32x2 | Self::Uint32x3 | Self::Uint32x4 =>
+                u32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]) as f32,
+            Self::Sint32 | Self::Sint32x2 | Self::Sint32x3 | Self::Sint32x4 =>
+                i32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]) as f32,
+            Self::Float64 | Self::Float64x2 | Self::Float64x3 | Self::Float64x4 =>
+                f64::from_le_bytes(bytes.try_into().unwrap()) as f32,
+        }
+    }
+
+    /// Decode the `index`th component of an unsigned integer element in `bytes`.
+    /// Return None if the type is not an unsigned integer type
+    pub fn component_u32(&self,bytes: &[u8],index: usize) -> Option<u32> {
+        let size = self.size() as usize / self.components();
+        let bytes = &bytes[index * size..(index + 1) * size];
+        Some(match self {
+            Self::Uint8x2 | Self::Uint8x4 => bytes[0] as u32,
+            Self::Uint16x2 | Self::Uint16x4 => u16::from_le_bytes([bytes[0],bytes[1]]) as u32,
+            Self::Uint32 | Self::Uint32x2 | Self::Uint32x3 | Self::Uint32x4 =>
+                u32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]),
+            _ => return None,
+        })
+    }
+
     pub fn try_from_gltf(data_type: gltf::accessor::DataType,dimensions: gltf::accessor::Dimensions) -> Option<Self>{
         Some(match (data_type,dimensions) {
             (gltf::accessor::DataType::U8,gltf::accessor::Dimensions::Vec2) => Self::Uint8x2,
@@ -132,3 +230,16 @@ impl DataType {
         })
     }
 }
+
+/// Convert an IEEE 754 half-precision float
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
@@ -1,11 +1,21 @@
 use xecs::{EntityId, World, ComponentRead, ComponentWrite};
+use crate::bounds::Aabb;
 
 mod vertices;
 mod attributes;
 mod data_type;
+mod morph;
+mod tangent;
+mod normals;
+mod values;
+mod topology;
+mod bounds;
 
 pub use vertices::Vertices;
 pub use data_type::DataType;
+pub use morph::{MorphTarget, MorphWeights};
+pub use normals::NormalWeighting;
+pub use values::VertexValue;
 pub use attributes::{
     Attribute,
     AttributeType
@@ -40,9 +50,211 @@ impl Mesh {
 #[derive(Debug,Clone)]
 pub struct MeshData {
     pub data: Vec<u8>,
+    pub assembly: Assembly,
     pub indices: Option<Indices>,
     pub vertices: Vertices,
-    pub material: EntityId,
+    /// The material entity, None means the default material
+    pub material: Option<EntityId>,
+    pub morph_targets: Vec<MorphTarget>,
+    /// The default weights of morph targets
+    pub morph_weights: Vec<f32>,
+    /// The bounds of positions given by the source, used by [aabb](MeshData::aabb) instead of positions
+    pub bounds: Option<Aabb>,
+}
+
+impl MeshData {
+    /// An empty mesh without vertices and indices
+    pub fn new(assembly: Assembly) -> Self {
+        MeshData {
+            data: Vec::new(),
+            assembly,
+            indices: None,
+            vertices: Vertices::Separate(Vec::new()),
+            material: None,
+            morph_targets: Vec::new(),
+            morph_weights: Vec::new(),
+            bounds: None,
+        }
+    }
+
+    /// Convert to interleaved vertex format.
+    /// Both the `data` and the attributes layout are rewritten.
+    /// Every attribute is aligned to `alignment` bytes, 1 means tightly packed.
+    pub fn into_interleaved(self,alignment: usize) -> Self {
+        let mut stride = 0;
+        let attributes = self.vertices.attributes()
+            .iter()
+            .map(|attribute| {
+                let offset = stride;
+                stride += align(attribute.data_type.size() as usize,alignment);
+                Attribute {
+                    offset,
+                    ..attribute.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let count = attributes.iter()
+            .map(|attribute| attribute.count)
+            .max()
+            .unwrap_or(0);
+        let vertices = Vertices::Interleaved(attributes,stride);
+        self.repack(vertices,stride * count)
+    }
+
+    /// Convert to separate vertex format.
+    /// Both the `data` and the attributes layout are rewritten.
+    /// Every attribute starts at a multiple of `alignment` bytes, 1 means tightly packed.
+    pub fn into_separate(self,alignment: usize) -> Self {
+        let mut size = 0;
+        let attributes = self.vertices.attributes()
+            .iter()
+            .map(|attribute| {
+                let offset = align(size,alignment);
+                size = offset + attribute.data_type.size() as usize * attribute.count;
+                Attribute {
+                    offset,
+                    ..attribute.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let vertices = Vertices::Separate(attributes);
+        self.repack(vertices,size)
+    }
+
+    pub fn attribute_info(&self,ty: AttributeType) -> Option<&Attribute> {
+        self.vertices.attributes()
+            .iter()
+            .find(|attribute| attribute.ty == ty)
+    }
+
+    /// Add an attribute with tightly packed `data`, or replace the existing attribute of `ty`.
+    /// The vertex format is kept.
+    pub fn set_attribute(&mut self,ty: AttributeType,data_type: DataType,data: &[u8]) {
+        let mut mesh_data = std::mem::take(self);
+        let is_interleaved = mesh_data.vertices.is_interleaved();
+        if ty == AttributeType::Position {
+            mesh_data.bounds = None;
+        }
+        match &mut mesh_data.vertices {
+            Vertices::Interleaved(attributes, _) | Vertices::Separate(attributes) =>
+                attributes.retain(|attribute| attribute.ty != ty),
+        }
+        let mut mesh_data = mesh_data.into_separate(4);
+        let offset = align(mesh_data.data.len(),4);
+        mesh_data.data.resize(offset,0);
+        mesh_data.data.extend_from_slice(data);
+        if let Vertices::Separate(attributes) = &mut mesh_data.vertices {
+            attributes.push(Attribute {
+                ty,
+                data_type,
+                count: data.len() / data_type.size() as usize,
+                offset,
+            });
+        }
+        *self = if is_interleaved {
+            mesh_data.into_interleaved(4)
+        } else {
+            mesh_data
+        };
+    }
+
+    /// The number of vertices
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.attributes()
+            .iter()
+            .map(|attribute| attribute.count)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The indices as u32, or `0..vertex_count` if the mesh is not indexed
+    pub(crate) fn vertex_indices(&self) -> Vec<u32> {
+        match &self.indices {
+            Some(Indices::U16(indices)) => indices.iter().map(|&index| index as u32).collect(),
+            Some(Indices::U32(indices)) => indices.clone(),
+            None => (0..self.vertex_count() as u32).collect(),
+        }
+    }
+
+    /// Replace the indices, using u16 if all vertices can be addressed by it
+    pub(crate) fn set_indices(&mut self,indices: Vec<u32>) {
+        self.indices = if self.vertex_count() <= u16::MAX as usize + 1 {
+            Some(Indices::U16(indices.into_iter().map(|index| index as u16).collect()))
+        } else {
+            Some(Indices::U32(indices))
+        };
+    }
+
+    /// Rebuild the vertices so that the new vertex `i` is the old vertex `mapping[i]`.
+    /// Morph targets are remapped too, and the indices are removed.
+    pub(crate) fn remap_vertices(&mut self,mapping: &[u32]) {
+        let mut size = 0;
+        let attributes = self.vertices.attributes()
+            .iter()
+            .map(|attribute| {
+                let offset = align(size,4);
+                size = offset + attribute.data_type.size() as usize * mapping.len();
+                Attribute {
+                    count: mapping.len(),
+                    offset,
+                    ..attribute.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let vertices = Vertices::Separate(attributes);
+        let mut data = vec![0;size];
+        for (src,dst) in self.vertices.attributes().iter().zip(vertices.attributes()) {
+            for (index,&old) in mapping.iter().enumerate() {
+                let src_range = self.vertices.element_range(src,old as usize);
+                let dst_range = vertices.element_range(dst,index);
+                data[dst_range].copy_from_slice(&self.data[src_range]);
+            }
+        }
+        let remap = |deltas: &mut Option<Vec<[f32; 3]>>| {
+            if let Some(deltas) = deltas {
+                *deltas = mapping.iter().map(|&old| deltas[old as usize]).collect();
+            }
+        };
+        for target in &mut self.morph_targets {
+            remap(&mut target.positions);
+            remap(&mut target.normals);
+            remap(&mut target.tangents);
+        }
+        let is_interleaved = self.vertices.is_interleaved();
+        self.data = data;
+        self.vertices = vertices;
+        self.indices = None;
+        if is_interleaved {
+            *self = std::mem::take(self).into_interleaved(4);
+        }
+    }
+
+    /// Copy every element to the new layout
+    fn repack(self,vertices: Vertices,size: usize) -> Self {
+        let mut data = vec![0;size];
+        for (src,dst) in self.vertices.attributes().iter().zip(vertices.attributes()) {
+            for index in 0..src.count {
+                let src_range = self.vertices.element_range(src,index);
+                let dst_range = vertices.element_range(dst,index);
+                data[dst_range].copy_from_slice(&self.data[src_range]);
+            }
+        }
+        MeshData {
+            data,
+            vertices,
+            ..self
+        }
+    }
+}
+
+impl Default for MeshData {
+    fn default() -> Self {
+        MeshData::new(Assembly::Triangles)
+    }
+}
+
+fn align(size: usize,alignment: usize) -> usize {
+    size.next_multiple_of(alignment.max(1))
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -63,3 +275,33 @@ pub enum Indices{
     U32(Vec<u32>)
 }
 
+
+/// Little-endian bytes of `values`, for building attributes in tests
+#[cfg(test)]
+pub(crate) fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MeshData, Assembly, Vertices, Attribute, AttributeType, DataType};
+
+    #[test]
+    fn repack() {
+        let attributes = vec![
+            Attribute { ty: AttributeType::Position, data_type: DataType::Float32, count: 2, offset: 0 },
+            Attribute { ty: AttributeType::Color, data_type: DataType::Unorm8x2, count: 2, offset: 8 },
+        ];
+        let mesh_data = MeshData {
+            data: vec![1,1,1,1, 2,2,2,2, 3,3, 4,4],
+            vertices: Vertices::Separate(attributes),
+            ..MeshData::new(Assembly::Points)
+        };
+        let interleaved = mesh_data.into_interleaved(4);
+        assert_eq!(interleaved.vertices.stride_of(&interleaved.vertices.attributes()[0]), 8);
+        assert_eq!(interleaved.data, vec![1,1,1,1, 3,3,0,0, 2,2,2,2, 4,4,0,0]);
+        let separate = interleaved.into_separate(1);
+        assert!(!separate.vertices.is_interleaved());
+        assert_eq!(separate.data, vec![1,1,1,1, 2,2,2,2, 3,3, 4,4]);
+    }
+}
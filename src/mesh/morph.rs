@@ -0,0 +1,106 @@
+use super::{MeshData, AttributeType, DataType};
+
+/// Per vertex deltas of a morph target
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct MorphTarget {
+    pub positions: Option<Vec<[f32; 3]>>,
+    pub normals: Option<Vec<[f32; 3]>>,
+    pub tangents: Option<Vec<[f32; 3]>>,
+}
+
+/// The weights of morph targets of a Mesh entity
+#[derive(Debug,Clone,PartialEq)]
+pub struct MorphWeights(pub Vec<f32>);
+
+impl MeshData {
+    /// Blend morph targets with `weights` and return the deformed mesh.
+    /// Only Float32x3 positions and normals and Float32x4 tangents are deformed
+    pub fn morph(&self,weights: &[f32]) -> MeshData {
+        let mut mesh_data = self.clone();
+        // The given bounds do not contain the deformed positions
+        mesh_data.bounds = None;
+        for attribute in self.vertices.attributes() {
+            let deltas = |target: &MorphTarget| match attribute.ty {
+                AttributeType::Position => target.positions.clone(),
+                AttributeType::Normal => target.normals.clone(),
+                AttributeType::Tangent => target.tangents.clone(),
+                _ => None,
+            };
+            let is_direction = attribute.ty != AttributeType::Position;
+            if !matches!(attribute.data_type,DataType::Float32x3 | DataType::Float32x4) {
+                continue;
+            }
+            let targets = self.morph_targets.iter()
+                .zip(weights)
+                .filter_map(|(target,weight)| Some((deltas(target)?,*weight)))
+                .collect::<Vec<_>>();
+            if targets.is_empty() {
+                continue;
+            }
+            for index in 0..attribute.count {
+                let range = self.vertices.element_range(attribute,index);
+                let bytes = &mut mesh_data.data[range.start..range.start + 12];
+                let mut value = [0.0f32; 3];
+                for (component,bytes) in value.iter_mut().zip(bytes.chunks_exact(4)) {
+                    *component = f32::from_le_bytes([bytes[0],bytes[1],bytes[2],bytes[3]]);
+                }
+                for (deltas,weight) in &targets {
+                    if let Some(delta) = deltas.get(index) {
+                        for (component,delta) in value.iter_mut().zip(delta) {
+                            *component += weight * delta;
+                        }
+                    }
+                }
+                if is_direction {
+                    let length = value.iter().map(|c| c * c).sum::<f32>().sqrt();
+                    if length > 0.0 {
+                        value.iter_mut().for_each(|c| *c /= length);
+                    }
+                }
+                for (component,bytes) in value.iter().zip(bytes.chunks_exact_mut(4)) {
+                    bytes.copy_from_slice(&component.to_le_bytes());
+                }
+            }
+        }
+        mesh_data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{MeshData, Vertices, AttributeType, DataType, f32_bytes};
+    use super::MorphTarget;
+
+    #[test]
+    fn blend() {
+        let mut mesh_data = MeshData {
+            vertices: Vertices::Interleaved(Vec::new(),0),
+            ..MeshData::default()
+        };
+        mesh_data.set_attribute(AttributeType::Position,DataType::Float32x3,&f32_bytes(&[0.0,0.0,0.0, 1.0,0.0,0.0]));
+        mesh_data.set_attribute(AttributeType::Normal,DataType::Float32x3,&f32_bytes(&[0.0,0.0,1.0, 0.0,0.0,1.0]));
+        mesh_data.set_attribute(AttributeType::TexCoord(0),DataType::Float32x2,&f32_bytes(&[0.5,0.5, 0.25,0.25]));
+        mesh_data.morph_targets = vec![
+            MorphTarget {
+                positions: Some(vec![[0.0,2.0,0.0],[0.0,0.0,0.0]]),
+                normals: Some(vec![[1.0,0.0,-1.0],[0.0,0.0,0.0]]),
+                tangents: None,
+            },
+            MorphTarget {
+                positions: Some(vec![[0.0,0.0,0.0],[4.0,0.0,0.0]]),
+                ..MorphTarget::default()
+            },
+        ];
+        assert!(mesh_data.vertices.is_interleaved());
+        let morphed = mesh_data.morph(&[0.5,0.25]);
+        assert_eq!(morphed.positions().unwrap().collect::<Vec<_>>(), vec![[0.0,1.0,0.0],[2.0,0.0,0.0]]);
+        // Normals are renormalized after blending
+        let normals = morphed.normals().unwrap().collect::<Vec<_>>();
+        let length = (0.5f32 * 0.5 + 0.5 * 0.5).sqrt();
+        assert_eq!(normals[0], [0.5 / length,0.0,0.5 / length]);
+        assert_eq!(normals[1], [0.0,0.0,1.0]);
+        // Other attributes are kept
+        assert_eq!(morphed.tex_coords(0).unwrap().collect::<Vec<_>>(), vec![[0.5,0.5],[0.25,0.25]]);
+        assert_eq!(mesh_data.morph(&[0.0,0.0]).data, mesh_data.data);
+    }
+}
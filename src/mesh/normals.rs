@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use nalgebra_glm as glm;
+use super::{MeshData, Assembly, AttributeType, DataType};
+
+/// How face normals are weighted in smooth normals
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum NormalWeighting {
+    /// Weighted by the area of faces
+    Area,
+    /// Weighted by the angle of faces at the vertex
+    Angle,
+}
+
+impl MeshData {
+    /// Compute flat normals of a triangle list.
+    /// The mesh is un-indexed so every triangle has its own vertices.
+    /// Return false if the mesh is not a triangle list or there are no positions.
+    pub fn compute_flat_normals(&mut self) -> bool {
+        if self.assembly != Assembly::Triangles || self.positions().is_none() {
+            return false;
+        }
+        let indices = self.vertex_indices();
+        self.remap_vertices(&indices);
+        let positions = self.positions().unwrap().collect::<Vec<_>>();
+        let normals = positions.chunks_exact(3)
+            .flat_map(|triangle| {
+                let [a,b,c] = [0,1,2].map(|i| glm::make_vec3(&triangle[i]));
+                [face_normal(&a,&b,&c);3]
+            })
+            .collect::<Vec<_>>();
+        self.set_normals(&normals);
+        true
+    }
+
+    /// Compute smooth normals of a triangle list.
+    /// Vertices at the same position share normals unless the angle between their faces
+    /// is greater than `crease_angle` in radians, in which case the vertex is split.
+    /// Return false if the mesh is not a triangle list or there are no positions.
+    pub fn compute_smooth_normals(&mut self,weighting: NormalWeighting,crease_angle: f32) -> bool {
+        if self.assembly != Assembly::Triangles {
+            return false;
+        }
+        let Some(positions) = self.positions().map(Iterator::collect::<Vec<_>>) else {
+            return false;
+        };
+        let indices = self.vertex_indices();
+        let triangles = indices.chunks_exact(3).collect::<Vec<_>>();
+        // face normal and the weights of three corners
+        let faces = triangles.iter()
+            .map(|triangle| {
+                let [a,b,c] = [0,1,2].map(|i| glm::make_vec3(&positions[triangle[i] as usize]));
+                let cross = glm::cross(&(b - a),&(c - a));
+                let normal = face_normal(&a,&b,&c);
+                let weights = match weighting {
+                    NormalWeighting::Area => [glm::length(&cross) * 0.5;3],
+                    NormalWeighting::Angle => [
+                        glm::angle(&(b - a),&(c - a)),
+                        glm::angle(&(c - b),&(a - b)),
+                        glm::angle(&(a - c),&(b - c)),
+                    ],
+                };
+                (normal,weights.map(|w| if w.is_nan() { 0.0 } else { w }))
+            })
+            .collect::<Vec<_>>();
+        // corners sharing the same position
+        let mut shared = HashMap::<[u32; 3],Vec<(usize,usize)>>::new();
+        for (face,triangle) in triangles.iter().enumerate() {
+            for (corner,index) in triangle.iter().enumerate() {
+                let key = positions[*index as usize].map(f32::to_bits);
+                shared.entry(key).or_default().push((face,corner));
+            }
+        }
+        let cos_crease = crease_angle.cos();
+        let mut corner_normals = vec![glm::vec3(0.0,0.0,0.0);indices.len()];
+        for corners in shared.values() {
+            for &(face,corner) in corners {
+                let normal = corners.iter()
+                    .filter(|(other,_)| glm::dot(&faces[face].0,&faces[*other].0) >= cos_crease)
+                    .fold(glm::vec3(0.0,0.0,0.0),|sum,(other,other_corner)| {
+                        sum + faces[*other].0 * faces[*other].1[*other_corner]
+                    });
+                corner_normals[face * 3 + corner] = if glm::length(&normal) > 0.0 {
+                    glm::normalize(&normal)
+                } else {
+                    faces[face].0
+                };
+            }
+        }
+        // split vertices whose corners have different normals
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut split = HashMap::<(u32,[u32; 3]),u32>::new();
+        let new_indices = indices.iter()
+            .zip(&corner_normals)
+            .map(|(index,normal)| {
+                let key = (*index,[normal.x.to_bits(),normal.y.to_bits(),normal.z.to_bits()]);
+                *split.entry(key).or_insert_with(|| {
+                    vertices.push(*index);
+                    normals.push(*normal);
+                    vertices.len() as u32 - 1
+                })
+            })
+            .collect::<Vec<_>>();
+        self.remap_vertices(&vertices);
+        self.set_indices(new_indices);
+        self.set_normals(&normals);
+        true
+    }
+
+    fn set_normals(&mut self,normals: &[glm::Vec3]) {
+        let data = normals.iter()
+            .flat_map(|normal| [normal.x,normal.y,normal.z])
+            .flat_map(f32::to_le_bytes)
+            .collect::<Vec<_>>();
+        self.set_attribute(AttributeType::Normal,DataType::Float32x3,&data);
+    }
+}
+
+/// The unit normal of a counter-clockwise triangle, zero if it is degenerate
+fn face_normal(a: &glm::Vec3,b: &glm::Vec3,c: &glm::Vec3) -> glm::Vec3 {
+    let cross = glm::cross(&(b - a),&(c - a));
+    if glm::length(&cross) > 0.0 {
+        glm::normalize(&cross)
+    } else {
+        cross
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{MeshData, Indices, AttributeType, DataType, f32_bytes};
+    use super::NormalWeighting;
+
+    /// Two triangles folded by 90 degrees along the x axis
+    fn fold() -> MeshData {
+        let mut mesh_data = MeshData {
+            indices: Some(Indices::U16(vec![0,1,2, 1,0,3])),
+            ..MeshData::default()
+        };
+        mesh_data.set_attribute(AttributeType::Position,DataType::Float32x3,
+            &f32_bytes(&[0.0,0.0,0.0, 1.0,0.0,0.0, 0.0,1.0,0.0, 0.0,0.0,1.0]));
+        mesh_data
+    }
+
+    #[test]
+    fn flat() {
+        let mut mesh_data = fold();
+        assert!(mesh_data.compute_flat_normals());
+        assert!(mesh_data.indices.is_none());
+        let normals = mesh_data.normals().unwrap().collect::<Vec<_>>();
+        assert_eq!(normals, vec![[0.0,0.0,1.0],[0.0,0.0,1.0],[0.0,0.0,1.0],[0.0,1.0,0.0],[0.0,1.0,0.0],[0.0,1.0,0.0]]);
+    }
+
+    #[test]
+    fn smooth() {
+        let mut mesh_data = fold();
+        assert!(mesh_data.compute_smooth_normals(NormalWeighting::Angle,std::f32::consts::PI));
+        assert_eq!(mesh_data.vertex_count(), 4);
+        let normals = mesh_data.normals().unwrap().collect::<Vec<_>>();
+        let shared = std::f32::consts::FRAC_1_SQRT_2;
+        assert_eq!(normals[0], [0.0,shared,shared]);
+        assert_eq!(normals[2], [0.0,0.0,1.0]);
+        // The shared edge is split by a crease angle below 90 degrees
+        let mut mesh_data = fold();
+        assert!(mesh_data.compute_smooth_normals(NormalWeighting::Area,1.0));
+        assert_eq!(mesh_data.vertex_count(), 6);
+    }
+}
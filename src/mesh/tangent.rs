@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use bevy_mikktspace::Geometry;
+use super::{MeshData, Assembly, AttributeType, DataType};
+
+struct MikkTSpace<'a> {
+    indices: &'a [u32],
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    tex_coords: Vec<[f32; 2]>,
+    /// The tangent of every face corner
+    tangents: Vec<[f32; 4]>,
+}
+
+impl MikkTSpace<'_> {
+    fn index(&self,face: usize,vert: usize) -> usize {
+        self.indices[face * 3 + vert] as usize
+    }
+}
+
+impl Geometry for MikkTSpace<'_> {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self,_face: usize) -> usize {
+        3
+    }
+
+    fn position(&self,face: usize,vert: usize) -> [f32; 3] {
+        self.positions[self.index(face,vert)]
+    }
+
+    fn normal(&self,face: usize,vert: usize) -> [f32; 3] {
+        self.normals[self.index(face,vert)]
+    }
+
+    fn tex_coord(&self,face: usize,vert: usize) -> [f32; 2] {
+        self.tex_coords[self.index(face,vert)]
+    }
+
+    fn set_tangent_encoded(&mut self,tangent: [f32; 4],face: usize,vert: usize) {
+        self.tangents[face * 3 + vert] = tangent;
+    }
+}
+
+impl MeshData {
+    /// Generate tangents with MikkTSpace and store them as a Float32x4 `AttributeType::Tangent`.
+    /// The mesh must be a triangle list with positions, normals and `AttributeType::TexCoord(tex_coord)`,
+    /// which should be the set used by the normal texture.
+    /// Vertices shared by faces with different tangents are split.
+    /// Return false if the mesh is not suitable for tangent generation.
+    pub fn generate_tangents(&mut self,tex_coord: u32) -> bool {
+        if self.assembly != Assembly::Triangles {
+            return false;
+        }
+        let (Some(positions),Some(normals),Some(tex_coords)) = (
+            self.positions().map(Iterator::collect::<Vec<_>>),
+            self.normals().map(Iterator::collect::<Vec<_>>),
+            self.tex_coords(tex_coord).map(Iterator::collect::<Vec<_>>)) else {
+            return false;
+        };
+        if normals.len() != positions.len() || tex_coords.len() != positions.len() {
+            return false;
+        }
+        let indices = self.vertex_indices();
+        let mut geometry = MikkTSpace {
+            indices: &indices,
+            tangents: vec![[1.0, 0.0, 0.0, 1.0];indices.len()],
+            positions,
+            normals,
+            tex_coords,
+        };
+        if !bevy_mikktspace::generate_tangents(&mut geometry) {
+            return false;
+        }
+        // split vertices whose corners have different tangents
+        let mut vertices = Vec::new();
+        let mut tangents = Vec::new();
+        let mut split = HashMap::<(u32,[u32; 4]),u32>::new();
+        let new_indices = indices.iter()
+            .zip(&geometry.tangents)
+            .map(|(index,tangent)| {
+                *split.entry((*index,tangent.map(f32::to_bits))).or_insert_with(|| {
+                    vertices.push(*index);
+                    tangents.push(*tangent);
+                    vertices.len() as u32 - 1
+                })
+            })
+            .collect::<Vec<_>>();
+        self.remap_vertices(&vertices);
+        self.set_indices(new_indices);
+        let data = tangents.iter()
+            .flatten()
+            .flat_map(|component| component.to_le_bytes())
+            .collect::<Vec<_>>();
+        self.set_attribute(AttributeType::Tangent,DataType::Float32x4,&data);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{MeshData, Vertices, Indices, AttributeType, DataType, f32_bytes};
+
+    #[test]
+    fn quad() {
+        let mut mesh_data = MeshData {
+            indices: Some(Indices::U16(vec![0,1,2, 0,2,3])),
+            vertices: Vertices::Interleaved(Vec::new(),0),
+            ..MeshData::default()
+        };
+        mesh_data.set_attribute(AttributeType::Position,DataType::Float32x3,
+            &f32_bytes(&[0.0,0.0,0.0, 1.0,0.0,0.0, 1.0,1.0,0.0, 0.0,1.0,0.0]));
+        mesh_data.set_attribute(AttributeType::Normal,DataType::Float32x3,
+            &f32_bytes(&[0.0,0.0,1.0, 0.0,0.0,1.0, 0.0,0.0,1.0, 0.0,0.0,1.0]));
+        mesh_data.set_attribute(AttributeType::TexCoord(0),DataType::Float32x2,
+            &f32_bytes(&[0.0,0.0, 1.0,0.0, 1.0,1.0, 0.0,1.0]));
+        assert!(mesh_data.generate_tangents(0));
+        assert!(mesh_data.vertices.is_interleaved());
+        let tangents = mesh_data.tangents().unwrap().collect::<Vec<_>>();
+        assert_eq!(tangents, vec![[1.0,0.0,0.0,1.0];4]);
+    }
+
+    #[test]
+    fn mirrored() {
+        let mut mesh_data = MeshData {
+            indices: Some(Indices::U16(vec![0,1,2, 0,2,3])),
+            ..MeshData::default()
+        };
+        mesh_data.set_attribute(AttributeType::Position,DataType::Float32x3,
+            &f32_bytes(&[0.0,0.0,0.0, 1.0,0.0,0.0, 1.0,1.0,0.0, 0.0,1.0,0.0]));
+        mesh_data.set_attribute(AttributeType::Normal,DataType::Float32x3,
+            &f32_bytes(&[0.0,0.0,1.0, 0.0,0.0,1.0, 0.0,0.0,1.0, 0.0,0.0,1.0]));
+        mesh_data.set_attribute(AttributeType::TexCoord(0),DataType::Float32x2,&f32_bytes(&[0.0;8]));
+        // u grows along +X in the first triangle and along -X in the second one
+        mesh_data.set_attribute(AttributeType::TexCoord(1),DataType::Float32x2,
+            &f32_bytes(&[0.0,0.0, 1.0,0.0, 1.0,1.0, 2.0,1.0]));
+        assert!(mesh_data.generate_tangents(1));
+        let tangents = mesh_data.tangents().unwrap().collect::<Vec<_>>();
+        let corners = mesh_data.vertex_indices()
+            .iter()
+            .map(|index| tangents[*index as usize])
+            .collect::<Vec<_>>();
+        assert_eq!(corners[..3], [[1.0,0.0,0.0,1.0];3]);
+        assert_eq!(corners[3..], [[-1.0,0.0,0.0,-1.0];3]);
+        // The shared vertices are split
+        assert_eq!(mesh_data.positions().unwrap().collect::<Vec<_>>(), vec![
+            [0.0,0.0,0.0], [1.0,0.0,0.0], [1.0,1.0,0.0],
+            [0.0,0.0,0.0], [1.0,1.0,0.0], [0.0,1.0,0.0],
+        ]);
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+use super::{MeshData, Assembly};
+
+impl MeshData {
+    /// Convert `TriangleStrip` and `TriangleFan` to `Triangles`, keeping the winding order.
+    /// Other assemblies are returned unchanged
+    pub fn into_triangle_list(mut self) -> Self {
+        let indices = self.vertex_indices();
+        let triangles = match self.assembly {
+            Assembly::TriangleStrip => (0..indices.len().saturating_sub(2))
+                .flat_map(|i| [indices[i],indices[i + 1 + i % 2],indices[i + 2 - i % 2]])
+                .collect(),
+            Assembly::TriangleFan => (1..indices.len().saturating_sub(1))
+                .flat_map(|i| [indices[i],indices[i + 1],indices[0]])
+                .collect(),
+            _ => return self,
+        };
+        self.assembly = Assembly::Triangles;
+        self.set_indices(triangles);
+        self
+    }
+
+    /// Convert `LineStrip` and `LineLoop` to `Lines`.
+    /// Other assemblies are returned unchanged
+    pub fn into_line_list(mut self) -> Self {
+        let indices = self.vertex_indices();
+        let mut lines = match self.assembly {
+            Assembly::LineStrip | Assembly::LineLoop => indices.windows(2)
+                .flat_map(|pair| [pair[0],pair[1]])
+                .collect::<Vec<_>>(),
+            _ => return self,
+        };
+        if self.assembly == Assembly::LineLoop && indices.len() > 1 {
+            lines.extend([indices[indices.len() - 1],indices[0]]);
+        }
+        self.assembly = Assembly::Lines;
+        self.set_indices(lines);
+        self
+    }
+
+    /// Get the unique edges of a triangle mesh, in the order they first appear.
+    /// Return None if the mesh is not made of triangles
+    pub fn wireframe_edges(&self) -> Option<Vec<[u32; 2]>> {
+        let indices = match self.assembly {
+            Assembly::Triangles => self.vertex_indices(),
+            Assembly::TriangleStrip | Assembly::TriangleFan => self.clone()
+                .into_triangle_list()
+                .vertex_indices(),
+            _ => return None,
+        };
+        let mut visited = HashSet::new();
+        Some(indices.chunks_exact(3)
+            .flat_map(|triangle| [[triangle[0],triangle[1]],[triangle[1],triangle[2]],[triangle[2],triangle[0]]])
+            .filter(|[a,b]| visited.insert((*a.min(b),*a.max(b))))
+            .collect())
+    }
+
+    /// Convert a triangle mesh to a `Lines` mesh of its edges.
+    /// Other assemblies are returned unchanged
+    pub fn into_wireframe(mut self) -> Self {
+        let Some(edges) = self.wireframe_edges() else {
+            return self;
+        };
+        self.assembly = Assembly::Lines;
+        self.set_indices(edges.into_iter().flatten().collect());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{MeshData, Assembly, Indices, AttributeType, DataType};
+
+    fn mesh_data(assembly: Assembly,indices: Vec<u16>) -> MeshData {
+        let mut mesh_data = MeshData {
+            indices: Some(Indices::U16(indices)),
+            ..MeshData::new(assembly)
+        };
+        mesh_data.set_attribute(AttributeType::Position,DataType::Float32x3,&[0;48]);
+        mesh_data
+    }
+
+    #[test]
+    fn convert() {
+        let strip = mesh_data(Assembly::TriangleStrip,vec![0,1,2,3]).into_triangle_list();
+        assert_eq!(strip.assembly, Assembly::Triangles);
+        assert_eq!(strip.vertex_indices(), vec![0,1,2, 1,3,2]);
+        let fan = mesh_data(Assembly::TriangleFan,vec![0,1,2,3]).into_triangle_list();
+        assert_eq!(fan.vertex_indices(), vec![1,2,0, 2,3,0]);
+        let line_loop = mesh_data(Assembly::LineLoop,vec![0,1,2]).into_line_list();
+        assert_eq!(line_loop.assembly, Assembly::Lines);
+        assert_eq!(line_loop.vertex_indices(), vec![0,1, 1,2, 2,0]);
+        let wireframe = mesh_data(Assembly::Triangles,vec![0,1,2, 0,2,3]).into_wireframe();
+        assert_eq!(wireframe.assembly, Assembly::Lines);
+        assert_eq!(wireframe.vertex_indices(), vec![0,1, 1,2, 2,0, 2,3, 3,0]);
+    }
+}
@@ -0,0 +1,109 @@
+use super::{MeshData, AttributeType, DataType};
+
+/// Types that elements of vertex attributes can be decoded into
+pub trait VertexValue: Sized {
+    /// Whether elements of `data_type` can be decoded into this type
+    fn accepts(data_type: DataType) -> bool;
+    /// Decode an element from `bytes` of `data_type`
+    fn decode(data_type: DataType,bytes: &[u8]) -> Self;
+}
+
+/// Any type with at least `N` components, normalized integers are converted to float.
+/// Extra components are dropped, such as the padding of widened 8 and 16 bits vec3
+impl<const N: usize> VertexValue for [f32; N] {
+    fn accepts(data_type: DataType) -> bool {
+        data_type.components() >= N
+    }
+
+    fn decode(data_type: DataType,bytes: &[u8]) -> Self {
+        std::array::from_fn(|index| data_type.component_f32(bytes,index))
+    }
+}
+
+impl VertexValue for f32 {
+    fn accepts(data_type: DataType) -> bool {
+        data_type.components() == 1
+    }
+
+    fn decode(data_type: DataType,bytes: &[u8]) -> Self {
+        data_type.component_f32(bytes,0)
+    }
+}
+
+/// Unsigned integer types with at least `N` components, such as joint indices
+impl<const N: usize> VertexValue for [u32; N] {
+    fn accepts(data_type: DataType) -> bool {
+        data_type.components() >= N && data_type.is_unsigned_integer()
+    }
+
+    fn decode(data_type: DataType,bytes: &[u8]) -> Self {
+        std::array::from_fn(|index| data_type.component_u32(bytes,index).unwrap())
+    }
+}
+
+impl MeshData {
+    /// Iterate over the elements of an attribute, work with both vertex formats.
+    /// Return None if the attribute does not exist or can not be decoded into `T`
+    pub fn attribute<T: VertexValue>(&self,ty: AttributeType) -> Option<impl Iterator<Item = T> + '_> {
+        let attribute = self.attribute_info(ty)?;
+        if !T::accepts(attribute.data_type) {
+            return None;
+        }
+        Some((0..attribute.count).map(move |index| {
+            T::decode(attribute.data_type,&self.data[self.vertices.element_range(attribute,index)])
+        }))
+    }
+
+    pub fn positions(&self) -> Option<impl Iterator<Item = [f32; 3]> + '_> {
+        self.attribute(AttributeType::Position)
+    }
+
+    pub fn normals(&self) -> Option<impl Iterator<Item = [f32; 3]> + '_> {
+        self.attribute(AttributeType::Normal)
+    }
+
+    /// Tangents with the handedness in w
+    pub fn tangents(&self) -> Option<impl Iterator<Item = [f32; 4]> + '_> {
+        self.attribute(AttributeType::Tangent)
+    }
+
+    pub fn tex_coords(&self,set: u32) -> Option<impl Iterator<Item = [f32; 2]> + '_> {
+        self.attribute(AttributeType::TexCoord(set))
+    }
+
+    pub fn joints(&self,set: u32) -> Option<impl Iterator<Item = [u32; 4]> + '_> {
+        self.attribute(AttributeType::Joints(set))
+    }
+
+    pub fn weights(&self,set: u32) -> Option<impl Iterator<Item = [f32; 4]> + '_> {
+        self.attribute(AttributeType::Weights(set))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{MeshData, Vertices, AttributeType, DataType};
+
+    #[test]
+    fn decode() {
+        let mut mesh_data = MeshData {
+            vertices: Vertices::Interleaved(Vec::new(),0),
+            ..MeshData::default()
+        };
+        mesh_data.set_attribute(AttributeType::TexCoord(0),DataType::Unorm16x2,&[0,0, 255,255, 255,255, 0,0]);
+        mesh_data.set_attribute(AttributeType::Normal,DataType::Snorm8x4,&[127,0,129,0, 128,0,0,0]);
+        mesh_data.set_attribute(AttributeType::Joints(0),DataType::Uint8x4,&[1,2,3,4, 5,6,7,8]);
+        assert!(mesh_data.vertices.is_interleaved());
+        let tex_coords = mesh_data.tex_coords(0).unwrap().collect::<Vec<_>>();
+        assert_eq!(tex_coords, vec![[0.0,1.0],[1.0,0.0]]);
+        let normals = mesh_data.attribute::<[f32; 4]>(AttributeType::Normal).unwrap().collect::<Vec<_>>();
+        assert_eq!(normals, vec![[1.0,0.0,-1.0,0.0],[-1.0,0.0,0.0,0.0]]);
+        let joints = mesh_data.joints(0).unwrap().collect::<Vec<_>>();
+        assert_eq!(joints, vec![[1,2,3,4],[5,6,7,8]]);
+        let normals = mesh_data.normals().unwrap().collect::<Vec<_>>();
+        assert_eq!(normals, vec![[1.0,0.0,-1.0],[-1.0,0.0,0.0]]);
+        // too few components or not an unsigned integer type
+        assert!(mesh_data.attribute::<[f32; 3]>(AttributeType::TexCoord(0)).is_none());
+        assert!(mesh_data.attribute::<[u32; 2]>(AttributeType::TexCoord(0)).is_none());
+    }
+}
@@ -9,18 +9,28 @@ pub enum Vertices{
 }
 
 impl Vertices{
-    /// Convert interleaved vertex format to separate vertex format
-    pub fn into_separate(self) -> Self {
+    pub fn attributes(&self) -> &[Attribute] {
         match self {
-            Vertices::Interleaved(_, _) => todo!(),
-            Vertices::Separate(_) => self,
+            Vertices::Interleaved(attributes, _) => attributes,
+            Vertices::Separate(attributes) => attributes,
         }
     }
 
-    pub fn into_interleaved(self) -> Self {
+    pub fn is_interleaved(&self) -> bool {
+        matches!(self,Vertices::Interleaved(_, _))
+    }
+
+    /// Get the distance in bytes between two elements of `attribute`
+    pub fn stride_of(&self,attribute: &Attribute) -> usize {
         match self {
-            Vertices::Interleaved(_, _) => self,
-            Vertices::Separate(_) => todo!(),
+            Vertices::Interleaved(_, stride) => *stride,
+            Vertices::Separate(_) => attribute.data_type.size() as usize,
         }
     }
-}
\ No newline at end of file
+
+    /// Get the byte range of the `index`th element of `attribute` in MeshData::data
+    pub fn element_range(&self,attribute: &Attribute,index: usize) -> std::ops::Range<usize> {
+        let begin = attribute.offset + index * self.stride_of(attribute);
+        begin..begin + attribute.data_type.size() as usize
+    }
+}
@@ -41,3 +41,9 @@ impl Node {
         self.parent = parent;
     }
 }
+
+impl Default for Node {
+    fn default() -> Self {
+        Node::new()
+    }
+}
@@ -0,0 +1,83 @@
+use nalgebra_glm as glm;
+use xecs::{EntityId, World};
+use crate::GlobalTransform3D;
+
+/// The skeleton of a skinned mesh
+#[derive(Debug,Clone,PartialEq)]
+pub struct Skin {
+    /// The joint entities, indexed by `AttributeType::Joints`
+    pub joints: Vec<EntityId>,
+    /// Transform mesh space to the local space of each joint
+    pub inverse_bind_matrices: Vec<glm::TMat4<f32>>,
+    /// The root of the joint hierarchy
+    pub skeleton: Option<EntityId>,
+}
+
+impl Skin {
+    pub fn new(joints: Vec<EntityId>,inverse_bind_matrices: Vec<glm::TMat4<f32>>) -> Self {
+        Skin {
+            joints,
+            inverse_bind_matrices,
+            skeleton: None,
+        }
+    }
+
+    /// Compute the joint palette in mesh space from the GlobalTransform3D of joints.
+    /// `mesh_transform` is the GlobalTransform3D of the skinned mesh entity.
+    /// Joints without GlobalTransform3D use identity matrix
+    pub fn joint_matrices(&self,world: &World,mesh_transform: &GlobalTransform3D) -> Vec<glm::TMat4<f32>> {
+        let inverse_mesh = glm::inverse(mesh_transform.matrix());
+        let globals = world.components_read::<GlobalTransform3D>()
+            .expect("GlobalTransform3D is not registered");
+        self.joints.iter()
+            .enumerate()
+            .map(|(index,joint)| {
+                let joint = globals.get(*joint)
+                    .map(|global| *global.matrix())
+                    .unwrap_or_else(glm::identity);
+                let inverse_bind = self.inverse_bind_matrices.get(index)
+                    .copied()
+                    .unwrap_or_else(glm::identity);
+                inverse_mesh * joint * inverse_bind
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra_glm as glm;
+    use xecs::World;
+    use crate::{Transform3D, GlobalTransform3D, propagate_transforms};
+    use super::Skin;
+
+    #[test]
+    fn joint_matrices() {
+        let mut world = World::new();
+        crate::init(&mut world);
+        let mut mesh_transform = Transform3D::new();
+        mesh_transform.move_to(0.0, 0.0, 1.0);
+        let mesh = world.create_entity()
+            .attach(mesh_transform)
+            .into_id();
+        // The joint is bound at (1, 0, 1) and moved up by 2
+        let mut joint_transform = Transform3D::new();
+        joint_transform.move_to(1.0, 2.0, 1.0);
+        joint_transform.rotate_z_by(0.5);
+        let joint = world.create_entity()
+            .attach(joint_transform)
+            .into_id();
+        let inverse_bind = glm::translation(&glm::vec3(-1.0, 0.0, 0.0));
+        let skin = Skin::new(vec![joint],vec![inverse_bind]);
+        propagate_transforms(&world);
+
+        let mesh_global = *world.entity_component_read::<GlobalTransform3D>(mesh).unwrap();
+        let palette = skin.joint_matrices(&world,&mesh_global);
+        let joint_global = *world.entity_component_read::<GlobalTransform3D>(joint).unwrap();
+        let expected = glm::inverse(mesh_global.matrix()) * joint_global.matrix() * inverse_bind;
+        assert_eq!(palette, vec![expected]);
+        // A vertex at the bind position of the joint follows it
+        let vertex = palette[0] * glm::vec4(1.0, 0.0, 0.0, 1.0);
+        assert!(glm::length(&(vertex.xyz() - glm::vec3(1.0, 2.0, 0.0))) < 1e-6);
+    }
+}
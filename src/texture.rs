@@ -2,7 +2,9 @@ use xecs::{EntityId, ComponentRead, World};
 use crate::Image;
 
 pub struct Texture {
+    /// The image entity
     pub image: EntityId,
+    /// The region of image in UV coordinates (x, y, width, height)
     pub view: [f32; 4],
     pub mag_filter: MagFilter,
     pub min_filter: MinFilter,
@@ -0,0 +1,183 @@
+use nalgebra_glm as glm;
+use xecs::{World, EntityId, query::{WithId, Without}, StorageRead, StorageWrite};
+use crate::Node;
+use super::Transform3D;
+
+/// The world-space transform, written by [propagate_transforms]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GlobalTransform3D {
+    matrix: glm::TMat4<f32>,
+    /// The local transform used in last propagation
+    local: Option<Transform3D>,
+    /// The parent matrix used in last propagation
+    parent: glm::TMat4<f32>,
+}
+
+impl GlobalTransform3D {
+    pub fn new() -> Self {
+        GlobalTransform3D {
+            matrix: glm::identity(),
+            local: None,
+            parent: glm::identity(),
+        }
+    }
+
+    /// Local-to-world matrix
+    pub fn matrix(&self) -> &glm::TMat4<f32> {
+        &self.matrix
+    }
+
+    pub fn translation(&self) -> glm::TVec3<f32> {
+        self.matrix.column(3).xyz()
+    }
+}
+
+impl Default for GlobalTransform3D {
+    fn default() -> Self {
+        GlobalTransform3D::new()
+    }
+}
+
+/// Compose Transform3D through Node hierarchies and write the result to GlobalTransform3D.
+/// GlobalTransform3D is attached to the entities with Transform3D if it's missing.
+/// Only the entities whose local transform or parent matrix changed since last propagation are recomputed,
+/// so moved and reparented subtrees are both updated.
+pub fn propagate_transforms(world: &World) {
+    let missing = world.query::<(Without<&GlobalTransform3D>,&Transform3D)>()
+        .with_id()
+        .map(|(id,_)| id)
+        .collect::<Vec<_>>();
+    for id in missing {
+        world.attach_component(id,GlobalTransform3D::new());
+    }
+    // Parentless nodes are roots even without transform
+    let mut roots = world.query::<&Node>()
+        .with_id()
+        .filter(|(_,node)| node.parent().is_none())
+        .map(|(id,_)| id)
+        .collect::<Vec<_>>();
+    roots.extend(world.query::<(Without<&Node>,&Transform3D)>()
+        .with_id()
+        .map(|(id,_)| id));
+    let nodes = world.components_read::<Node>()
+        .expect("Node is not registered");
+    let transforms = world.components_read::<Transform3D>()
+        .expect("Transform3D is not registered");
+    let mut globals = world.components_write::<GlobalTransform3D>()
+        .expect("GlobalTransform3D is not registered");
+    for root in roots {
+        propagate(root,&glm::identity(),&transforms,&nodes,&mut globals);
+    }
+}
+
+fn propagate(id: EntityId,
+             parent: &glm::TMat4<f32>,
+             transforms: &StorageRead<Transform3D>,
+             nodes: &StorageRead<Node>,
+             globals: &mut StorageWrite<GlobalTransform3D>) {
+    // Entities without transform pass the parent transform to their children
+    let matrix = match (transforms.get(id),globals.get_mut(id)) {
+        (Some(local),Some(global)) => {
+            if global.local.as_ref() != Some(local) || global.parent != *parent {
+                global.matrix = parent * local.model_matrix();
+                global.local = Some(*local);
+                global.parent = *parent;
+            }
+            global.matrix
+        },
+        _ => *parent,
+    };
+    if let Some(node) = nodes.get(id) {
+        for &child in node.children() {
+            propagate(child,&matrix,transforms,nodes,globals);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xecs::World;
+    use crate::{Transform3D, Node};
+    use super::{GlobalTransform3D, propagate_transforms};
+
+    #[test]
+    fn propagate() {
+        let mut world = World::new();
+        crate::init(&mut world);
+        let mut parent_transform = Transform3D::new();
+        parent_transform.move_to(1.0, 0.0, 0.0);
+        parent_transform.scale_to(2.0, 2.0, 2.0);
+        let parent = world.create_entity()
+            .attach(parent_transform)
+            .into_id();
+        let mut child_transform = Transform3D::new();
+        child_transform.move_to(0.0, 1.0, 0.0);
+        let child = world.create_entity()
+            .attach(child_transform)
+            .attach(Node::from_parent(parent))
+            .into_id();
+        let mut node = Node::new();
+        node.add_child(child);
+        world.attach_component(parent,node);
+
+        propagate_transforms(&world);
+        let global = *world.entity_component_read::<GlobalTransform3D>(child).unwrap();
+        assert_eq!(global.translation().as_slice(), &[1.0, 2.0, 0.0]);
+
+        world.entity_component_write::<Transform3D>(parent).unwrap().move_by(0.0, 0.0, 1.0);
+        propagate_transforms(&world);
+        let global = *world.entity_component_read::<GlobalTransform3D>(child).unwrap();
+        assert_eq!(global.translation().as_slice(), &[1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn root_without_transform() {
+        let mut world = World::new();
+        crate::init(&mut world);
+        let root = world.create_entity()
+            .attach(Node::new())
+            .into_id();
+        let mut transform = Transform3D::new();
+        transform.move_to(1.0, 0.0, 0.0);
+        let child = world.create_entity()
+            .attach(transform)
+            .attach(Node::from_parent(root))
+            .into_id();
+        world.entity_component_write::<Node>(root).unwrap().add_child(child);
+        propagate_transforms(&world);
+        assert!(world.entity_component_read::<GlobalTransform3D>(root).is_none());
+        let global = *world.entity_component_read::<GlobalTransform3D>(child).unwrap();
+        assert_eq!(global.translation().as_slice(), &[1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn reparent() {
+        let mut world = World::new();
+        crate::init(&mut world);
+        let mut parents = Vec::new();
+        for x in [1.0,5.0] {
+            let mut transform = Transform3D::new();
+            transform.move_to(x, 0.0, 0.0);
+            parents.push(world.create_entity()
+                .attach(transform)
+                .attach(Node::new())
+                .into_id());
+        }
+        let child = world.create_entity()
+            .attach(Transform3D::new())
+            .attach(Node::from_parent(parents[0]))
+            .into_id();
+        world.entity_component_write::<Node>(parents[0]).unwrap().add_child(child);
+        propagate_transforms(&world);
+        let global = *world.entity_component_read::<GlobalTransform3D>(child).unwrap();
+        assert_eq!(global.translation().as_slice(), &[1.0, 0.0, 0.0]);
+
+        // Neither local transform changes
+        world.entity_component_write::<Node>(parents[0]).unwrap().remove_child(child);
+        world.entity_component_write::<Node>(parents[1]).unwrap().add_child(child);
+        world.entity_component_write::<Node>(child).unwrap().set_parent(Some(parents[1]));
+        propagate_transforms(&world);
+        let global = *world.entity_component_read::<GlobalTransform3D>(child).unwrap();
+        assert_eq!(global.translation().as_slice(), &[5.0, 0.0, 0.0]);
+    }
+}
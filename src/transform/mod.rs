@@ -1,6 +1,8 @@
 mod transform2d;
 mod transform3d;
+mod global_transform3d;
 
 pub use transform3d::Transform3D;
 pub use transform2d::Transform2D;
+pub use global_transform3d::{GlobalTransform3D, propagate_transforms};
 
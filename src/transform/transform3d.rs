@@ -12,8 +12,8 @@ pub struct Transform3D {
 impl Transform3D {
     pub(in crate) fn from_gltf_transform(transform : gltf::scene::Transform) -> Self {
         match transform {
-            #[allow(unused)]
-            gltf::scene::Transform::Matrix { matrix } => todo!(),
+            gltf::scene::Transform::Matrix { matrix } =>
+                Transform3D::from_matrix(&glm::make_mat4(matrix.as_flattened())),
             gltf::scene::Transform::Decomposed { translation, rotation, scale } => {
                 let mut transform = Transform3D::new();
                 transform.translation = glm::make_vec3(&translation);
@@ -24,6 +24,42 @@ impl Transform3D {
         }
     }
 
+    /// Decompose an affine matrix into translation, rotation and scale.
+    /// A negative determinant is represented by a negative X scale.
+    pub fn from_matrix(matrix : &glm::TMat4<f32>) -> Self {
+        let translation = matrix.column(3).xyz();
+        let mut basis = glm::mat4_to_mat3(matrix);
+        let mut scale = glm::vec3(
+            basis.column(0).norm(),
+            basis.column(1).norm(),
+            basis.column(2).norm());
+        if basis.determinant() < 0.0 {
+            scale.x = -scale.x;
+        }
+        // The rotation is lost if two or more axes are scaled to zero
+        let rotation = if scale.iter().filter(|s| **s == 0.0).count() > 1 {
+            glm::quat_identity()
+        } else {
+            for (mut column,s) in basis.column_iter_mut().zip(scale.iter()) {
+                if *s != 0.0 {
+                    column /= *s;
+                }
+            }
+            // A single zero axis is completed by the cross product of the other axes
+            if let Some(axis) = scale.iter().position(|s| *s == 0.0) {
+                let column = basis.column((axis + 1) % 3).cross(&basis.column((axis + 2) % 3));
+                basis.set_column(axis,&column);
+            }
+            glm::quat_normalize(&glm::mat3_to_quat(&basis))
+        };
+        Transform3D {
+            translation,
+            center: glm::vec3(0.0, 0.0, 0.0),
+            scale,
+            rotation,
+        }
+    }
+
     pub fn new() -> Self {
         Transform3D {
             translation: glm::make_vec3(&[0.0, 0.0, 0.0]),
@@ -156,4 +192,25 @@ mod tests {
         assert_eq!(trans.pitch().to_degrees(), 43_f32);
     }
 
+    #[test]
+    fn from_matrix() {
+        let mut trans = Transform3D::new();
+        trans.move_to(1.0, 2.0, 3.0);
+        trans.scale_to(2.0, -3.0, 0.5);
+        trans.rotate_by((1.0, 1.0, 0.0), 1.2);
+        let matrix = trans.model_matrix();
+        let decomposed = Transform3D::from_matrix(&matrix);
+        assert!(decomposed.scale.x < 0.0);
+        assert!((decomposed.model_matrix() - matrix).abs().max() < 1e-5);
+
+        // The rotation survives a single zero scale axis
+        let mut flat = Transform3D::new();
+        flat.rotate_z_by(std::f32::consts::FRAC_PI_2);
+        flat.scale_to(1.0, 0.0, 1.0);
+        let matrix = flat.model_matrix();
+        let decomposed = Transform3D::from_matrix(&matrix);
+        assert_eq!(decomposed.scale.y, 0.0);
+        assert!((decomposed.model_matrix() - matrix).abs().max() < 1e-5);
+    }
+
 }